@@ -56,7 +56,6 @@ export function SendNftDialog(props: SendNftDialogProps) {
                     request: {
                         method: 'transferNft',
                         params: {
-                            nft_address: nft.address,
                             nft_id: nft.id,
                             nft_resource: nft.collection,
                             destination_public_key: recipient,
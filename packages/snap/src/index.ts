@@ -15,7 +15,7 @@ import {
   sendIndexerRequest,
   substateExists,
 } from './tari_indexer_client';
-import { getRistrettoKeyPair } from './keys';
+import { getPublicKey, withSecretKey } from './keys';
 import {
   ConfidentialTransferRequest,
   GetConfidentialVaultBalancesRequest,
@@ -26,12 +26,21 @@ import {
   TransferRequest,
 } from './types';
 import {
+  getEpochWindow,
   getTransactionResult,
   sendInstruction,
   sendTransaction,
 } from './transactions';
 import { mintAccountNft, transferNft } from './nfts';
-import { getState, setState } from './state';
+import { createFungibleToken } from './tokens';
+import {
+  addOutputKeyIndex,
+  getOutputKeyIndexes,
+  getState,
+  getValueLookupTable,
+  setState,
+  setValueLookupTable,
+} from './state';
 
 declare let snap: any;
 
@@ -45,6 +54,19 @@ const fs = require('fs');
 // - https://github.com/mdn/webassembly-examples/tree/06556204f687c00a5d9d3ab55805204cbb711d0c/js-api-examples
 let wasm: tari_wallet_lib.InitOutput;
 
+// Building the lookup table for viewable balances is expensive, so it is built once and kept in the snap state
+let valueLookupTable: Uint8Array | undefined;
+
+async function loadValueLookupTable() {
+  const storedTable = await getValueLookupTable();
+  if (storedTable) {
+    return storedTable;
+  }
+  const table = tari_wallet_lib.build_value_lookup_table(undefined);
+  await setValueLookupTable(table);
+  return table;
+}
+
 /**
  * Load and initialize the WASM module. This modifies the global `wasm`
  * variable, with the instantiated module.
@@ -71,7 +93,7 @@ async function getAccountData(
   _request: JsonRpcRequest<Json[] | Record<string, Json>>,
 ) {
   const accountIndex = 0;
-  const { secret_key, public_key } = await getRistrettoKeyPair(accountIndex);
+  const public_key = await getPublicKey(accountIndex);
 
   const component_address =
     tari_wallet_lib.get_account_component_address(public_key);
@@ -93,7 +115,7 @@ async function getAccountData(
 
   console.log(JSON.stringify(vault_ids, null, 2));
 
-  const resources = await Promise.all(
+  const resources = await withSecretKey(accountIndex, (secret_key) => Promise.all(
     vault_ids.map(async (v: any) => {
       const res = await getSubstate(v);
       const vault_substate = res.substate.substate.Vault;
@@ -121,7 +143,7 @@ async function getAccountData(
         `Unknown resource container type ${JSON.stringify(container)}`,
       );
     }),
-  );
+  ));
 
   console.log('resoutce', JSON.stringify(resources, null, 2));
 
@@ -155,7 +177,6 @@ async function transfer(
   }
 
   const accountIndex = 0;
-  const { secret_key, public_key } = await getRistrettoKeyPair(accountIndex);
 
   // check if the destination account exists
   const dest_account_component = tari_wallet_lib.get_account_component_address(
@@ -164,49 +185,36 @@ async function transfer(
   const dest_account_exists = await substateExists(dest_account_component);
   const create_dest_account = !dest_account_exists;
 
+  const epoch_window = await getEpochWindow();
+
   // build and sign transaction using the wasm lib
-  const transaction_json = tari_wallet_lib.create_transfer_transaction(
-    secret_key,
-    destination_public_key,
-    create_dest_account,
-    resource_address,
-    BigInt(amount),
-    BigInt(fee),
+  const { transaction: transaction_json } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_transfer_transaction(
+      secret_key,
+      destination_public_key,
+      create_dest_account,
+      resource_address,
+      BigInt(amount),
+      BigInt(fee),
+      epoch_window,
+    ),
   );
   const transaction = JSON.parse(transaction_json);
 
-  const account_component =
-    tari_wallet_lib.get_account_component_address(public_key);
-
   // send the transaction to the indexer
   const submit_method = 'submit_transaction';
   const submit_params = {
     transaction,
     is_dry_run: false,
-    required_substates: [
-      {
-        substate_id: account_component,
-        version: null,
-      },
-      {
-        substate_id: resource_address,
-        version: null,
-      },
-    ],
+    required_substates: tari_wallet_lib.infer_required_substates(transaction_json, []),
   };
-  if (dest_account_exists) {
-    submit_params.required_substates.push({
-      substate_id: dest_account_component,
-      version: null,
-    });
-  }
 
   // TODO: keep polling the indexer until we get a result for the transaction
   return await sendIndexerRequest(submit_method, submit_params);
 }
 
 async function getAccountVaults(accountIndex: number) {
-  const { public_key } = await getRistrettoKeyPair(accountIndex);
+  const public_key = await getPublicKey(accountIndex);
 
   const component_address =
     tari_wallet_lib.get_account_component_address(public_key);
@@ -274,8 +282,6 @@ async function confidentialTransfer(
     return null;
   }
 
-  const { secret_key, public_key } = await getRistrettoKeyPair(accountIndex);
-
   // check if the destination account exists
   const dest_account_component = tari_wallet_lib.get_account_component_address(
     destination_public_key,
@@ -283,48 +289,43 @@ async function confidentialTransfer(
   const dest_account_exists = await substateExists(dest_account_component);
   const create_dest_account = !dest_account_exists;
 
+  const epoch_window = await getEpochWindow();
+  const output_key_indexes = await getOutputKeyIndexes();
+
   // build and sign the confidential transaction using the wasm lib
-  const transaction_json = tari_wallet_lib.create_confidential_transfer_transaction(
-    secret_key,
-    vault_id, //source_vault_id: &str
-    vault_substate, //source_vault_js: JsValue
-    destination_public_key,
-    create_dest_account,
-    resource_address,
-    resource_substate, //resource_substate_js: JsValue,
-    BigInt(amount),
-    BigInt(fee),
-    undefined, //undefined, //proof_from_resource: Option<String>,
-    false, // output_to_revealed: bool,
-    {"ConfidentialOnly": null}, //input_selection_js: JsValue,
+  const { transaction: transaction_json, change_output_key } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_confidential_transfer_transaction(
+      secret_key,
+      vault_id, //source_vault_id: &str
+      vault_substate, //source_vault_js: JsValue
+      destination_public_key,
+      create_dest_account,
+      resource_address,
+      resource_substate, //resource_substate_js: JsValue,
+      BigInt(amount),
+      BigInt(fee),
+      undefined, //undefined, //proof_from_resource: Option<String>,
+      false, // output_to_revealed: bool,
+      {"ConfidentialOnly": null}, //input_selection_js: JsValue,
+      {"LargestFirst": null}, //coin_selection_js: JsValue,
+      output_key_indexes, //output_key_indexes_js: JsValue,
+      epoch_window, //epoch_window_js: JsValue,
+    ),
   );
   const transaction = JSON.parse(transaction_json);
 
-  const account_component =
-    tari_wallet_lib.get_account_component_address(public_key);
+  // the mask of the change output is derived from its key index, which is needed to spend it later
+  if (change_output_key) {
+    await addOutputKeyIndex(change_output_key);
+  }
 
   // send the transaction to the indexer
   const submit_method = 'submit_transaction';
   const submit_params = {
     transaction,
     is_dry_run: false,
-    required_substates: [
-      {
-        substate_id: account_component,
-        version: null,
-      },
-      {
-        substate_id: resource_address,
-        version: null,
-      },
-    ],
+    required_substates: tari_wallet_lib.infer_required_substates(transaction_json, []),
   };
-  if (dest_account_exists) {
-    submit_params.required_substates.push({
-      substate_id: dest_account_component,
-      version: null,
-    });
-  }
 
   // TODO: keep polling the indexer until we get a result for the transaction
   return await sendIndexerRequest(submit_method, submit_params);
@@ -334,7 +335,7 @@ async function getTransactions(
   _request: JsonRpcRequest<Json[] | Record<string, Json>>,
 ) {
   const accountIndex = 0;
-  const { public_key } = await getRistrettoKeyPair(accountIndex);
+  const public_key = await getPublicKey(accountIndex);
   const component_address =
     tari_wallet_lib.get_account_component_address(public_key);
 
@@ -374,19 +375,24 @@ async function getFreeTestCoins(
   }
 
   const accountIndex = 0;
-  const { secret_key, public_key } = await getRistrettoKeyPair(accountIndex);
+  const public_key = await getPublicKey(accountIndex);
   const component_address =
     tari_wallet_lib.get_account_component_address(public_key);
 
   const accountExists = await substateExists(component_address);
   const is_new_account = !accountExists;
 
+  const epoch_window = await getEpochWindow();
+
   // build and sign transaction using the wasm lib
-  const transaction_json = tari_wallet_lib.create_free_test_coins_transaction(
-    is_new_account,
-    secret_key,
-    BigInt(amount),
-    BigInt(fee),
+  const { transaction: transaction_json } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_free_test_coins_transaction(
+      is_new_account,
+      secret_key,
+      BigInt(amount),
+      BigInt(fee),
+      epoch_window,
+    ),
   );
   const transaction = JSON.parse(transaction_json);
  
@@ -452,10 +458,10 @@ async function getRistrettoPublicKey(
   req: JsonRpcRequest<Json[] | Record<string, Json>>,
 ) {
   const params = req.params as GetRistrettoPublicKeyRequest;
-  const keypair = await getRistrettoKeyPair(params.index);
+  const public_key = await getPublicKey(params.index);
 
   return {
-    public_key: keypair.public_key,
+    public_key,
   };
 }
 
@@ -465,8 +471,13 @@ async function getConfidentialVaultBalances(
   const params = req.params as GetConfidentialVaultBalancesRequest;
   const vault_substate = await getSubstate(params.vault_id);
   const vault = vault_substate.substate.substate.Vault;
-  const keypair = await getRistrettoKeyPair(params.view_key_id);
-  const balances = tari_wallet_lib.view_vault_balance(vault, params.minimum_expected_value, params.maximum_expected_value, keypair.secret_key);
+  if (!valueLookupTable) {
+    valueLookupTable = await loadValueLookupTable();
+  }
+  const lookupTable = valueLookupTable;
+  const balances = await withSecretKey(params.view_key_id, (secret_key) =>
+    tari_wallet_lib.view_vault_balance(vault, params.minimum_expected_value, params.maximum_expected_value, secret_key, lookupTable),
+  );
 
   return balances;
 }
@@ -511,6 +522,8 @@ export const onRpcRequest: OnRpcRequestHandler = async ({
       return mintAccountNft(wasm, request);
     case 'transferNft':
       return transferNft(wasm, request);
+    case 'createFungibleToken':
+      return createFungibleToken(wasm, request);
     case 'getSubstate':
       return getSubstateHandler(request);
     case 'listSubstates':
@@ -547,6 +560,7 @@ export const onUserInput: OnUserInputHandler = async ({ id, event }) => {
     event.name === 'indexer-url'
   ) {
     const indexer_url = event.value;
-    setState({ indexer_url })
+    const state = await getState();
+    setState({ ...state, indexer_url })
   }
 };
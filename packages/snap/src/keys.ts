@@ -4,8 +4,10 @@ import * as tari_wallet_lib from './tari_wallet_lib';
 // TODO: decide on a value for Tari
 const TARI_COIN_TYPE = 12345678;
 
+// The secret key stays inside the WASM memory, callers must `free()` it once they are done with it,
+// prefer `withSecretKey` which always does
 export type RistrettoKeyPair = {
-  secret_key: string;
+  secret_key: tari_wallet_lib.SecretKeyHandle;
   public_key: string;
 };
 
@@ -26,13 +28,33 @@ export async function getRistrettoKeyPair(
     throw new Error('Failed to derive private key');
   }
 
-  const secret_key =
-    tari_wallet_lib.build_ristretto_private_key(ecdsaPrivateKey);
-  const public_key =
-    tari_wallet_lib.build_ristretto_public_key(ecdsaPrivateKey);
+  // TODO: migrate to the latest derivation version once existing accounts are moved over
+  const version = tari_wallet_lib.KeyDerivationVersion.V0;
+  const secret_key = tari_wallet_lib.SecretKeyHandle.from_metamask_key(
+    ecdsaPrivateKey,
+    version,
+  );
+  const public_key = secret_key.public_key();
 
   return {
     secret_key,
     public_key,
   };
 }
+
+// Calls `use` with the secret key at `index`, freeing the key even if `use` throws
+export async function withSecretKey<T>(
+  index: number,
+  use: (secret_key: tari_wallet_lib.SecretKeyHandle) => T | Promise<T>,
+): Promise<T> {
+  const { secret_key } = await getRistrettoKeyPair(index);
+  try {
+    return await use(secret_key);
+  } finally {
+    secret_key.free();
+  }
+}
+
+export async function getPublicKey(index: number): Promise<string> {
+  return withSecretKey(index, (secret_key) => secret_key.public_key());
+}
@@ -1,119 +1,65 @@
 import { Json, JsonRpcRequest } from '@metamask/snaps-sdk';
 import * as tari_wallet_lib from './tari_wallet_lib';
-import { substateExists } from './tari_indexer_client';
-import { getRistrettoKeyPair } from './keys';
-import { SendInstructionRequest, SendTransactionRequest } from './types';
-import {
-  sendInstructionInternal,
-  sendTransactionInternal,
-  waitForTransactionResult,
-} from './transactions';
-
-const ACCOUNT_NFT_TEMPLATE =
-  '0000000000000000000000000000000000000000000000000000000000000001';
+import { sendIndexerRequest, substateExists } from './tari_indexer_client';
+import { getPublicKey, withSecretKey } from './keys';
+import { confirmTransaction, getEpochWindow } from './transactions';
 
 export type MintAccountNftRequest = {
   metadata: Object;
   fee: number;
 };
 
-async function createAccountNftComponent(wasm: tari_wallet_lib.InitOutput, fee: number) {
-  // send the transaction to create the account nft component
-  const accountIndex = 0;
-  const { public_key } = await getRistrettoKeyPair(accountIndex);
-  const account_component_address =
-    tari_wallet_lib.get_account_component_address(public_key);
-  const owner_token = await tari_wallet_lib.get_owner_token(public_key);
-  const encoded_fee = await tari_wallet_lib.encode_amount(fee);
-  let sendTransactionRequest: SendTransactionRequest = {
-    instructions: [
-      {
-        CallFunction: {
-          template_address: ACCOUNT_NFT_TEMPLATE,
-          function: 'create',
-          args: [{ Literal: owner_token }],
-        }
-      },
-    ],
-    fee_instructions: [
-      {
-        CallMethod: {
-          component_address: account_component_address,
-          method: 'pay_fee',
-          args: [{ Literal: encoded_fee }],
-        },
-      },
-    ],
-    input_refs: [],
-    required_substates: [{
-      substate_id: account_component_address,
-      version: null,
-    }],
-    is_dry_run: false,
-  };
-
-  // wait for the result of the account nft component
-  const { transaction_id } = await sendTransactionInternal(wasm, sendTransactionRequest);
-  const { execution_result } = await waitForTransactionResult(transaction_id);
-
-  // extract and return the account nft component address
-  let { up_substates } = execution_result.finalize.result.Accept;
-  for (let i = 0; i < up_substates.length; i++) {
-    const [id, substate] = up_substates[i];
-    if (id.Component && substate.substate.Component.template_address === ACCOUNT_NFT_TEMPLATE) {
-      return id.Component;
-    }
-  }
-
-  throw new Error("Account NFT component was not created");
-}
-
 export async function mintAccountNft(
-  wasm: tari_wallet_lib.InitOutput,
+  _wasm: tari_wallet_lib.InitOutput,
   request: JsonRpcRequest<Json[] | Record<string, Json>>,
 ) {
   const params = request.params as unknown as MintAccountNftRequest;
   const { metadata, fee } = params;
 
   const accountIndex = 0;
-  const { public_key } = await getRistrettoKeyPair(accountIndex);
-  const account_component_address =
-    tari_wallet_lib.get_account_component_address(public_key);
-
-  let nft_component_address = await createAccountNftComponent(wasm, fee);
-
-  // include the input substates of the transaction (account and nft component)
-  const required_substates = [
-    { substate_id: account_component_address, version: null },
-    { substate_id: nft_component_address, version: null },
-  ];
-
-  // build and send the mint transaction
-  let encoded_metadata = tari_wallet_lib.encode_metadata(metadata);
-  let sendInstructionRequest: SendInstructionRequest = {
-    instructions: [
-      {
-        CallMethod: {
-          component_address: nft_component_address,
-          method: 'mint',
-          args: [{ Literal: encoded_metadata }],
-        },
-      },
-    ],
-    fee_instructions: [],
-    input_refs: [],
-    required_substates,
+  const public_key = await getPublicKey(accountIndex);
+
+  // the account nft component has a fixed address, so it can be created and minted from in the same transaction
+  const nft_component_address =
+    tari_wallet_lib.get_account_nft_component_address(public_key);
+  const nft_component_exists = await substateExists(nft_component_address);
+  const epoch_window = await getEpochWindow();
+
+  // build and sign transaction using the wasm lib
+  const { transaction: transaction_json } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_account_nft_mint_transaction(
+      secret_key,
+      !nft_component_exists,
+      metadata,
+      BigInt(fee),
+      epoch_window,
+    ),
+  );
+
+  const userConfirmation = await confirmTransaction(transaction_json, public_key);
+  if (!userConfirmation) {
+    return null;
+  }
+
+  // a component created by the transaction cannot be one of its inputs
+  const required_substates = tari_wallet_lib.infer_required_substates(
+    transaction_json,
+    nft_component_exists ? [] : [nft_component_address],
+  );
+
+  // send the transaction to the indexer
+  const submit_method = 'submit_transaction';
+  const submit_params = {
+    transaction: JSON.parse(transaction_json),
     is_dry_run: false,
-    fee,
-    dump_account: account_component_address,
+    required_substates,
   };
 
-  const result = await sendInstructionInternal(wasm, sendInstructionRequest);
-  return { result };
+  const result = await sendIndexerRequest(submit_method, submit_params);
+  return { result, component_address: nft_component_address };
 }
 
 export type TransferNftRequest = {
-  nft_address: string;
   nft_resource: string;
   nft_id: string;
   destination_public_key: string;
@@ -121,19 +67,14 @@ export type TransferNftRequest = {
 };
 
 export async function transferNft(
-  wasm: tari_wallet_lib.InitOutput,
+  _wasm: tari_wallet_lib.InitOutput,
   request: JsonRpcRequest<Json[] | Record<string, Json>>,
 ) {
   const params = request.params as TransferNftRequest;
-  const { nft_address, nft_resource, nft_id, destination_public_key, fee } =
-    params;
+  const { nft_resource, nft_id, destination_public_key, fee } = params;
 
   const accountIndex = 0;
-  const { public_key } = await getRistrettoKeyPair(accountIndex);
-  const account_component_address =
-    tari_wallet_lib.get_account_component_address(public_key);
-
-  let instructions : object[] = [];
+  const public_key = await getPublicKey(accountIndex);
 
   // create the recipient account if it does not exist already
   const destination_account_address =
@@ -141,73 +82,33 @@ export async function transferNft(
   const destination_account_exists = await substateExists(
     destination_account_address,
   );
-  if (!destination_account_exists) {
-    instructions.push({
-      CreateAccount: {
-        owner_public_key: destination_public_key,
-        workspace_bucket: null,
-      },
-    });
-  }
+  const epoch_window = await getEpochWindow();
+
+  // build and sign transaction using the wasm lib
+  const { transaction: transaction_json } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_nft_transfer_transaction(
+      secret_key,
+      destination_public_key,
+      !destination_account_exists,
+      nft_resource,
+      [nft_id],
+      BigInt(fee),
+      epoch_window,
+    ),
+  );
 
-  // include the input substates of the transaction
-  let required_substates = [
-    { substate_id: account_component_address, version: null },
-    { substate_id: nft_address, version: null },
-  ];
-  if (destination_account_exists) {
-    required_substates.push({
-      substate_id: destination_account_address,
-      version: null,
-    });
+  const userConfirmation = await confirmTransaction(transaction_json, public_key);
+  if (!userConfirmation) {
+    return null;
   }
 
-  // encode the NFT address parts to be passed as template arguments
-  const parsed_nft_resource = await tari_wallet_lib.parse_resource_address(
-    nft_resource,
-  );
-  const encoded_nft_id = await tari_wallet_lib.encode_non_fungible_id(nft_id);
-  const encoded_fee = await tari_wallet_lib.encode_amount(BigInt(fee));
-
-  // build and send the mint transaction
-  // TODO: the instruction type should accept strings as well
-  let key = [97, 95, 98, 117, 99, 107, 101, 116];
-  let sendTransactionRequest: SendTransactionRequest = {
-    instructions: [
-      ...instructions,
-      {
-        CallMethod: {
-          component_address: account_component_address,
-          method: 'withdraw_non_fungible',
-          args: [parsed_nft_resource, { Literal: encoded_nft_id }],
-        },
-      },
-      {
-        PutLastInstructionOutputOnWorkspace: {
-          key,
-        },
-      },
-      {
-        CallMethod: {
-          component_address: destination_account_address,
-          method: 'deposit',
-          args: [{ Workspace: key }],
-        },
-      },
-    ],
-    fee_instructions: [
-      {
-        CallMethod: {
-          component_address: account_component_address,
-          method: 'pay_fee',
-          args: [{ Literal: encoded_fee }],
-        },
-      }
-    ],
-    input_refs: [],
-    required_substates,
+  // send the transaction to the indexer
+  const submit_method = 'submit_transaction';
+  const submit_params = {
+    transaction: JSON.parse(transaction_json),
     is_dry_run: false,
+    required_substates: tari_wallet_lib.infer_required_substates(transaction_json, []),
   };
 
-  return await sendTransactionInternal(wasm, sendTransactionRequest);
+  return await sendIndexerRequest(submit_method, submit_params);
 }
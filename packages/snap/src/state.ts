@@ -1,7 +1,10 @@
+import { Buffer } from "buffer";
 import { DEFAULT_TARI_INDEXER_URL } from "./env";
 
 export type State = {
   indexer_url: string;
+  // key index of each confidential change output of the account, by hex commitment, as a decimal string
+  output_key_indexes?: Record<string, string>;
 };
 
 const DEFAULT_STATE = {
@@ -23,3 +26,46 @@ export async function getState(): Promise<State> {
 
     return (state as State | null) ?? DEFAULT_STATE;
 }
+
+export type OutputKeyIndex = {
+  commitment: string;
+  key_index: string;
+};
+
+export async function getOutputKeyIndexes(): Promise<Record<string, string>> {
+    const { output_key_indexes } = await getState();
+    return output_key_indexes ?? {};
+}
+
+export async function addOutputKeyIndex({ commitment, key_index }: OutputKeyIndex) {
+    const state = await getState();
+    await setState({
+      ...state,
+      output_key_indexes: { ...state.output_key_indexes, [commitment]: key_index },
+    });
+}
+
+// The value lookup table is not secret and is large, so it is kept in the unencrypted state apart from the settings
+type LookupTableState = {
+  value_lookup_table: string;
+};
+
+export async function getValueLookupTable(): Promise<Uint8Array | null> {
+    const state = await snap.request({
+      method: 'snap_manageState',
+      params: { operation: 'get', encrypted: false },
+    });
+
+    const table = (state as LookupTableState | null)?.value_lookup_table;
+    return table ? new Uint8Array(Buffer.from(table, 'base64')) : null;
+}
+
+export async function setValueLookupTable(table: Uint8Array) {
+    const newState: LookupTableState = {
+      value_lookup_table: Buffer.from(table).toString('base64'),
+    };
+    await snap.request({
+      method: 'snap_manageState',
+      params: { operation: 'update', newState, encrypted: false },
+    });
+}
@@ -56,3 +56,8 @@ export async function substateExists(substate_address: string) {
     return false;
   }
 }
+
+export async function getCurrentEpoch(): Promise<number> {
+  const { current_epoch } = await sendIndexerRequest('get_epoch_manager_stats', {});
+  return current_epoch;
+}
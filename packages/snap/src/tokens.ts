@@ -0,0 +1,74 @@
+import { Json, JsonRpcRequest } from '@metamask/snaps-sdk';
+import * as tari_wallet_lib from './tari_wallet_lib';
+import { sendIndexerRequest } from './tari_indexer_client';
+import { getPublicKey, withSecretKey } from './keys';
+import { confirmTransaction, getEpochWindow } from './transactions';
+
+export type CreateFungibleTokenRequest = {
+  template_address: string;
+  token: {
+    symbol: string;
+    divisibility: number;
+    initial_supply: number;
+    metadata?: { key: string; value: string }[];
+    owner_rule?: Object;
+    confidential?: boolean;
+    view_key?: string;
+  };
+  fee: number;
+};
+
+export async function createFungibleToken(
+  _wasm: tari_wallet_lib.InitOutput,
+  request: JsonRpcRequest<Json[] | Record<string, Json>>,
+) {
+  const params = request.params as unknown as CreateFungibleTokenRequest;
+  const { template_address, token, fee } = params;
+
+  const accountIndex = 0;
+  const public_key = await getPublicKey(accountIndex);
+  const epoch_window = await getEpochWindow();
+
+  // build and sign transaction using the wasm lib
+  const { transaction: transaction_json } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_fungible_token_transaction(
+      secret_key,
+      template_address,
+      token,
+      BigInt(fee),
+      epoch_window,
+    ),
+  );
+
+  // the transaction is signed, so it is not sent anywhere before the user confirms it
+  const userConfirmation = await confirmTransaction(transaction_json, public_key);
+  if (!userConfirmation) {
+    return null;
+  }
+
+  const transaction = JSON.parse(transaction_json);
+  const required_substates = tari_wallet_lib.infer_required_substates(transaction_json, []);
+
+  // the address of the new resource is only assigned on execution, so it is taken from a dry run
+  const dry_run = await sendIndexerRequest('submit_transaction', {
+    transaction,
+    is_dry_run: true,
+    required_substates,
+  });
+  const execution_result = dry_run.result?.Finalized?.execution_result;
+  if (!execution_result) {
+    throw new Error('The dry run of the token creation did not finalize');
+  }
+  const resource_address = tari_wallet_lib.get_created_resource_address(execution_result.finalize);
+
+  // send the transaction to the indexer
+  const submit_method = 'submit_transaction';
+  const submit_params = {
+    transaction,
+    is_dry_run: false,
+    required_substates,
+  };
+
+  const result = await sendIndexerRequest(submit_method, submit_params);
+  return { result, resource_address };
+}
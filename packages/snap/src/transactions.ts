@@ -1,8 +1,8 @@
 import { Json, JsonRpcRequest } from '@metamask/snaps-sdk';
 import { heading, panel, text } from '@metamask/snaps-sdk';
 import * as tari_wallet_lib from './tari_wallet_lib';
-import { sendIndexerRequest } from './tari_indexer_client';
-import { getRistrettoKeyPair } from './keys';
+import { getCurrentEpoch, sendIndexerRequest } from './tari_indexer_client';
+import { getPublicKey, withSecretKey } from './keys';
 import { SendInstructionRequest, SendTransactionRequest } from './types';
 
 const POLLING_INTERVAL_MILLIS = 500;
@@ -24,19 +24,27 @@ export async function waitForTransactionResult(transaction_id: string, timeout_m
   throw new Error(`Timeout waiting for transaction "${transaction_id}"`);
 }
 
-export async function sendTransactionInternal(
-  _wasm: tari_wallet_lib.InitOutput,
-  request: SendTransactionRequest,
-) {
-  const {
-    fee_instructions,
-    instructions,
-    input_refs,
-    required_substates,
-    is_dry_run,
-  } = request;
+// Signed transactions are only accepted by the network for a few epochs, so they cannot be replayed later
+export async function getEpochWindow() {
+  const current_epoch = await getCurrentEpoch();
+  return tari_wallet_lib.epoch_window_from_current_epoch(BigInt(current_epoch), undefined);
+}
+
+// Step descriptions contain strings chosen by the website, e.g. method names, which must not add their own formatting
+function escapeMarkdown(value: string) {
+  return value.replace(/[\\`*_~[\]()#>|!-]/g, '\\$&');
+}
 
-  const userConfirmation = await snap.request({
+// Shows the steps of a transaction, given as JSON or as its instructions, and asks the user to confirm it
+export async function confirmTransaction(transaction: string | object, public_key: string) {
+  const summary = tari_wallet_lib.summarize_transaction(transaction, public_key);
+  // highlight the steps that use the user's own account
+  const steps = summary.steps.map((step: { description: string, touches_own_account: boolean }) => {
+    const description = escapeMarkdown(step.description);
+    return text(step.touches_own_account ? `**${description}**` : description);
+  });
+
+  return await snap.request({
     method: 'snap_dialog',
     params: {
       type: 'confirmation',
@@ -45,24 +53,46 @@ export async function sendTransactionInternal(
         text(
           `This website requests a transaction from your account, do you want to proceed?.`,
         ),
-        text(`**Fee Instructions:** ${JSON.stringify(fee_instructions)}`),
-        text(`**Instructions:** ${JSON.stringify(instructions)}`),
+        ...steps,
+        text(`Valid from epoch ${summary.min_epoch} to epoch ${summary.max_epoch}`),
       ]),
     },
   });
+}
+
+export async function sendTransactionInternal(
+  _wasm: tari_wallet_lib.InitOutput,
+  request: SendTransactionRequest,
+) {
+  const {
+    fee_instructions,
+    instructions,
+    input_refs,
+    is_dry_run,
+  } = request;
+  const required_substates = request.required_substates
+    ?? tari_wallet_lib.infer_required_substates({ fee_instructions, instructions }, []);
+
+  const accountIndex = 0;
+  const public_key = await getPublicKey(accountIndex);
+  const epoch_window = await getEpochWindow();
+  const userConfirmation = await confirmTransaction(
+    { fee_instructions, instructions, ...epoch_window },
+    public_key,
+  );
   if (!userConfirmation) {
     return null;
   }
 
-  const accountIndex = 0;
-  const { secret_key } = await getRistrettoKeyPair(accountIndex);
-
   // build and sign transaction using the wasm lib
-  const transaction_json = tari_wallet_lib.create_transaction(
-    secret_key,
-    instructions,
-    fee_instructions,
-    input_refs,
+  const { transaction: transaction_json } = await withSecretKey(accountIndex, (secret_key) =>
+    tari_wallet_lib.create_transaction(
+      secret_key,
+      instructions,
+      fee_instructions,
+      input_refs,
+      epoch_window,
+    ),
   );
   const transaction = JSON.parse(transaction_json);
 
@@ -21,7 +21,8 @@ export type SendTransactionRequest = {
   fee_instructions: object[];
   instructions: object[];
   input_refs: object[];
-  required_substates: object[];
+  // inferred from the instructions when omitted
+  required_substates?: object[];
   is_dry_run: boolean;
 };
 
@@ -29,7 +30,8 @@ export type SendInstructionRequest = {
   fee_instructions: object[];
   instructions: object[];
   input_refs: object[];
-  required_substates: object[];
+  // inferred from the instructions when omitted
+  required_substates?: object[];
   is_dry_run: boolean;
   fee: number;
   dump_account: string;
@@ -0,0 +1,310 @@
+//! Encoding of template calls from the template ABI.
+//! Plain JSON values are type checked against the parameter types of the function, so callers do not need to know
+//! how each argument is encoded.
+
+use std::convert::TryFrom;
+
+use serde_json::Value;
+use tari_engine_types::instruction::Instruction;
+use tari_template_abi::{FunctionDef, TemplateDef, Type};
+use tari_template_lib::args::Arg;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::ComponentAddress;
+
+use crate::arg_encoding::{encode_typed_arg, TypedArg};
+use crate::error::WalletLibError;
+use crate::metadata::MetadataField;
+
+const SELF_ARG: &str = "self";
+/// Key of the JSON object used to pass a workspace value instead of a literal, e.g. `{ "workspace": "bucket" }`.
+/// Only buckets and proofs are passed this way, any other parameter takes the object as data.
+const WORKSPACE_KEY: &str = "workspace";
+
+pub fn build_call_function_instruction(
+    template_address: TemplateAddress,
+    template_def: &TemplateDef,
+    function: &str,
+    values: Vec<Value>,
+) -> Result<Instruction, WalletLibError> {
+    let function_def = find_function(template_def, function)?;
+    if is_method(function_def) {
+        return Err(WalletLibError::invalid_argument(
+            function,
+            "is a method, it must be called on a component",
+        ));
+    }
+    Ok(Instruction::CallFunction {
+        template_address,
+        function: function.to_string(),
+        args: encode_call_args(function_def, values)?,
+    })
+}
+
+pub fn build_call_method_instruction(
+    component_address: ComponentAddress,
+    template_def: &TemplateDef,
+    method: &str,
+    values: Vec<Value>,
+) -> Result<Instruction, WalletLibError> {
+    let function_def = find_function(template_def, method)?;
+    if !is_method(function_def) {
+        return Err(WalletLibError::invalid_argument(
+            method,
+            "is a function, it must be called on the template",
+        ));
+    }
+    Ok(Instruction::CallMethod {
+        component_address,
+        method: method.to_string(),
+        args: encode_call_args(function_def, values)?,
+    })
+}
+
+/// Type checks the values against the parameters of the function (without `self`) and encodes them
+pub fn encode_call_args(function_def: &FunctionDef, values: Vec<Value>) -> Result<Vec<Arg>, WalletLibError> {
+    let params: Vec<_> = function_def
+        .arguments
+        .iter()
+        .filter(|arg| arg.name != SELF_ARG)
+        .collect();
+    if params.len() != values.len() {
+        return Err(WalletLibError::invalid_argument(
+            &function_def.name,
+            format!("expected {} arguments but got {}", params.len(), values.len()),
+        ));
+    }
+
+    params
+        .into_iter()
+        .zip(values)
+        .map(|(param, value)| {
+            let path = format!("{}.{}", function_def.name, param.name);
+            let typed = match workspace_reference(&value) {
+                Some(key) if is_workspace_type(&param.arg_type) => TypedArg::Workspace(key.to_string()),
+                _ => typed_value(&param.arg_type, value, &path)?,
+            };
+            encode_typed_arg(typed, &path)
+        })
+        .collect()
+}
+
+fn find_function<'a>(template_def: &'a TemplateDef, name: &str) -> Result<&'a FunctionDef, WalletLibError> {
+    template_def.get_function(name).ok_or_else(|| {
+        WalletLibError::invalid_argument(
+            "function",
+            format!("template '{}' has no function '{}'", template_def.template_name(), name),
+        )
+    })
+}
+
+fn is_method(function_def: &FunctionDef) -> bool {
+    function_def
+        .arguments
+        .first()
+        .map_or(false, |arg| arg.name == SELF_ARG)
+}
+
+/// Buckets and proofs only exist in the workspace of the transaction, they cannot be passed as literals
+fn is_workspace_type(ty: &Type) -> bool {
+    match ty {
+        Type::Other { name } => matches!(name.trim(), "Bucket" | "Proof"),
+        _ => false,
+    }
+}
+
+fn workspace_reference(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(map) if map.len() == 1 => map.get(WORKSPACE_KEY).and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+fn typed_value(ty: &Type, value: Value, path: &str) -> Result<TypedArg, WalletLibError> {
+    let mismatch = |value: &Value| {
+        WalletLibError::invalid_argument(
+            path,
+            format!("expected {} but got {}", type_name(ty), json_type_name(value)),
+        )
+    };
+
+    let typed = match ty {
+        Type::Bool => TypedArg::Bool(value.as_bool().ok_or_else(|| mismatch(&value))?),
+        Type::I8 => TypedArg::I8(integer(&value, path, ty)?),
+        Type::I16 => TypedArg::I16(integer(&value, path, ty)?),
+        Type::I32 => TypedArg::I32(integer(&value, path, ty)?),
+        Type::I64 => TypedArg::I64(integer(&value, path, ty)?),
+        Type::U8 => TypedArg::U8(integer(&value, path, ty)?),
+        Type::U16 => TypedArg::U16(integer(&value, path, ty)?),
+        Type::U32 => TypedArg::U32(integer(&value, path, ty)?),
+        Type::U64 => TypedArg::U64(integer(&value, path, ty)?),
+        Type::String => TypedArg::String(string(value, path, ty)?),
+        Type::Vec(inner) => match value {
+            Value::Array(values) => TypedArg::Vec(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, v)| typed_value(inner, v, &format!("{}[{}]", path, i)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            value => return Err(mismatch(&value)),
+        },
+        Type::Tuple(types) => match value {
+            Value::Array(values) if values.len() == types.len() => TypedArg::Tuple(
+                types
+                    .iter()
+                    .zip(values)
+                    .enumerate()
+                    .map(|(i, (ty, v))| typed_value(ty, v, &format!("{}[{}]", path, i)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Array(values) => {
+                return Err(WalletLibError::invalid_argument(
+                    path,
+                    format!("expected a tuple of {} elements but got {}", types.len(), values.len()),
+                ))
+            },
+            value => return Err(mismatch(&value)),
+        },
+        Type::Other { name } => typed_named_value(name, value, path)?,
+        ty => {
+            return Err(WalletLibError::invalid_argument(
+                path,
+                format!("parameters of type {} are not supported", type_name(ty)),
+            ))
+        },
+    };
+    Ok(typed)
+}
+
+/// Types the ABI only knows by name
+fn typed_named_value(name: &str, value: Value, path: &str) -> Result<TypedArg, WalletLibError> {
+    let name: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+    let named = Type::Other { name: name.clone() };
+
+    if let Some(inner) = name.strip_prefix("Option<").and_then(|n| n.strip_suffix('>')) {
+        return match value {
+            Value::Null => Ok(TypedArg::Option(None)),
+            value => {
+                let inner = typed_named_value(inner, value, path)?;
+                Ok(TypedArg::Option(Some(Box::new(inner))))
+            },
+        };
+    }
+
+    let typed = match name.as_str() {
+        "bool" => typed_value(&Type::Bool, value, path)?,
+        "i8" => typed_value(&Type::I8, value, path)?,
+        "i16" => typed_value(&Type::I16, value, path)?,
+        "i32" => typed_value(&Type::I32, value, path)?,
+        "i64" => typed_value(&Type::I64, value, path)?,
+        "u8" => typed_value(&Type::U8, value, path)?,
+        "u16" => typed_value(&Type::U16, value, path)?,
+        "u32" => typed_value(&Type::U32, value, path)?,
+        "u64" => typed_value(&Type::U64, value, path)?,
+        "String" => typed_value(&Type::String, value, path)?,
+        "Amount" => TypedArg::Amount(integer(&value, path, &named)?),
+        "ComponentAddress" => TypedArg::ComponentAddress(string(value, path, &named)?),
+        "ResourceAddress" => TypedArg::ResourceAddress(string(value, path, &named)?),
+        "VaultId" => TypedArg::VaultId(string(value, path, &named)?),
+        "NonFungibleAddress" => TypedArg::NonFungibleAddress(string(value, path, &named)?),
+        "TemplateAddress" => TypedArg::TemplateAddress(string(value, path, &named)?),
+        "NonFungibleId" => TypedArg::NonFungibleId(string(value, path, &named)?),
+        "RistrettoPublicKeyBytes" => TypedArg::PublicKey(string(value, path, &named)?),
+        "Metadata" => match value {
+            Value::Object(map) => TypedArg::Metadata(
+                map.into_iter()
+                    .map(|(key, value)| match value {
+                        Value::String(value) => Ok(MetadataField { key, value }),
+                        value => Err(WalletLibError::invalid_argument(
+                            &format!("{}.{}", path, key),
+                            format!("expected a string but got {}", json_type_name(&value)),
+                        )),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            value => {
+                return Err(WalletLibError::invalid_argument(
+                    path,
+                    format!("expected Metadata but got {}", json_type_name(&value)),
+                ))
+            },
+        },
+        "Bucket" | "Proof" => {
+            return Err(WalletLibError::invalid_argument(
+                path,
+                format!("a {} can only be passed from the workspace, e.g. {{ \"workspace\": \"name\" }}", name),
+            ))
+        },
+        _ => {
+            return Err(WalletLibError::invalid_argument(
+                path,
+                format!("parameters of type {} are not supported", name),
+            ))
+        },
+    };
+    Ok(typed)
+}
+
+fn integer<T: TryFrom<i128>>(value: &Value, path: &str, ty: &Type) -> Result<T, WalletLibError> {
+    // Integers that do not fit in a JavaScript number can be passed as strings
+    let integer = match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i128)),
+        Value::String(s) => s.parse::<i128>().ok(),
+        _ => None,
+    };
+    let integer = integer.ok_or_else(|| {
+        WalletLibError::invalid_argument(
+            path,
+            format!("expected {} but got {}", type_name(ty), json_type_name(value)),
+        )
+    })?;
+    T::try_from(integer).map_err(|_| {
+        WalletLibError::invalid_argument(path, format!("{} is out of range for {}", integer, type_name(ty)))
+    })
+}
+
+fn string(value: Value, path: &str, ty: &Type) -> Result<String, WalletLibError> {
+    match value {
+        Value::String(s) => Ok(s),
+        value => Err(WalletLibError::invalid_argument(
+            path,
+            format!("expected {} but got {}", type_name(ty), json_type_name(&value)),
+        )),
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Unit => "()".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::I8 => "i8".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::I128 => "i128".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::String => "String".to_string(),
+        Type::Vec(inner) => format!("Vec<{}>", type_name(inner)),
+        Type::Tuple(types) => format!("({})", types.iter().map(type_name).collect::<Vec<_>>().join(", ")),
+        Type::Other { name } => name.clone(),
+    }
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
@@ -0,0 +1,62 @@
+//! Minting into the account NFT component of a user, creating the component in the same transaction if needed.
+
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_engine_types::instruction::Instruction;
+use tari_template_builtin::ACCOUNT_NFT_TEMPLATE_ADDRESS;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::prelude::ComponentAddress;
+
+use crate::component::{
+    account_address_from_public_key, account_nft_address_from_public_key, owner_token_from_public_key,
+};
+use crate::error::WalletLibError;
+use crate::metadata::{encode_metadata_fields, MetadataField};
+
+const MINTED_NFT_BUCKET: &str = "minted_nft";
+
+#[derive(Debug, Clone)]
+pub struct AccountNftMint {
+    /// Predicted address of the account NFT component of the owner
+    pub component_address: ComponentAddress,
+    pub instructions: Vec<Instruction>,
+}
+
+/// Instructions that mint an NFT with `metadata` and deposit it into the account of `owner_public_key`.
+/// The component address is derived from the owner public key, so when `create_component` is set the component is
+/// created and minted from in the same transaction, without waiting for the address of the new component.
+pub fn build_account_nft_mint(
+    owner_public_key: &RistrettoPublicKey,
+    create_component: bool,
+    metadata: Vec<MetadataField>,
+) -> Result<AccountNftMint, WalletLibError> {
+    let component_address = account_nft_address_from_public_key(owner_public_key);
+    let account_address = account_address_from_public_key(owner_public_key);
+
+    let mut instructions = vec![];
+    if create_component {
+        instructions.push(Instruction::CallFunction {
+            template_address: ACCOUNT_NFT_TEMPLATE_ADDRESS,
+            function: "create".to_string(),
+            args: args![owner_token_from_public_key(owner_public_key)?],
+        });
+    }
+    instructions.push(Instruction::CallMethod {
+        component_address,
+        method: "mint".to_string(),
+        args: vec![Arg::Literal(encode_metadata_fields(metadata)?)],
+    });
+    instructions.push(Instruction::PutLastInstructionOutputOnWorkspace {
+        key: MINTED_NFT_BUCKET.as_bytes().to_vec(),
+    });
+    instructions.push(Instruction::CallMethod {
+        component_address: account_address,
+        method: "deposit".to_string(),
+        args: args![Workspace(MINTED_NFT_BUCKET)],
+    });
+
+    Ok(AccountNftMint {
+        component_address,
+        instructions,
+    })
+}
@@ -0,0 +1,255 @@
+//! Encoding of instruction arguments from a typed JSON description.
+//! Each argument is `{ "type": ..., "value": ... }`, so JavaScript callers do not need to pre-encode every value
+//! separately and wrap it in `{ Literal: ... }`.
+
+use std::mem;
+use std::str::FromStr;
+
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Serialize, Serializer};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_crypto::tari_utilities::ByteArray;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::args::Arg;
+use tari_template_lib::models::{TemplateAddress, VaultId};
+use tari_template_lib::prelude::{
+    Amount, ComponentAddress, Metadata, NonFungibleAddress, NonFungibleId, ResourceAddress, RistrettoPublicKeyBytes,
+};
+
+use crate::error::WalletLibError;
+use crate::metadata::{metadata_from_fields, MetadataField};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum TypedArg {
+    Amount(i64),
+    ComponentAddress(String),
+    ResourceAddress(String),
+    VaultId(String),
+    /// Resource address and non fungible id separated by a space
+    NonFungibleAddress(String),
+    TemplateAddress(String),
+    /// Any substate address, which is encoded as the specific address type
+    SubstateId(String),
+    PublicKey(String),
+    NonFungibleId(String),
+    String(String),
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Bytes(Vec<u8>),
+    Metadata(Vec<MetadataField>),
+    /// Name of a value put on the workspace by a previous instruction. Only allowed as a top level argument.
+    Workspace(String),
+    Tuple(Vec<TypedArg>),
+    Vec(Vec<TypedArg>),
+    Option(Option<Box<TypedArg>>),
+}
+
+/// Parsed argument value, serialized exactly as the equivalent Rust type would be
+#[derive(Debug, Clone)]
+enum LiteralValue {
+    Amount(Amount),
+    ComponentAddress(ComponentAddress),
+    ResourceAddress(ResourceAddress),
+    VaultId(VaultId),
+    NonFungibleAddress(NonFungibleAddress),
+    TemplateAddress(TemplateAddress),
+    SubstateId(SubstateId),
+    PublicKey(RistrettoPublicKeyBytes),
+    NonFungibleId(NonFungibleId),
+    String(String),
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Bytes(Vec<u8>),
+    Metadata(Metadata),
+    Tuple(Vec<LiteralValue>),
+    Vec(Vec<LiteralValue>),
+    Option(Option<Box<LiteralValue>>),
+}
+
+impl Serialize for LiteralValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            LiteralValue::Amount(v) => v.serialize(serializer),
+            LiteralValue::ComponentAddress(v) => v.serialize(serializer),
+            LiteralValue::ResourceAddress(v) => v.serialize(serializer),
+            LiteralValue::VaultId(v) => v.serialize(serializer),
+            LiteralValue::NonFungibleAddress(v) => v.serialize(serializer),
+            LiteralValue::TemplateAddress(v) => v.serialize(serializer),
+            LiteralValue::SubstateId(v) => serialize_substate_id(v, serializer),
+            LiteralValue::PublicKey(v) => v.serialize(serializer),
+            LiteralValue::NonFungibleId(v) => v.serialize(serializer),
+            LiteralValue::String(v) => v.serialize(serializer),
+            LiteralValue::Bool(v) => v.serialize(serializer),
+            LiteralValue::U8(v) => v.serialize(serializer),
+            LiteralValue::U16(v) => v.serialize(serializer),
+            LiteralValue::U32(v) => v.serialize(serializer),
+            LiteralValue::U64(v) => v.serialize(serializer),
+            LiteralValue::I8(v) => v.serialize(serializer),
+            LiteralValue::I16(v) => v.serialize(serializer),
+            LiteralValue::I32(v) => v.serialize(serializer),
+            LiteralValue::I64(v) => v.serialize(serializer),
+            LiteralValue::Bytes(v) => v.serialize(serializer),
+            LiteralValue::Metadata(v) => v.serialize(serializer),
+            LiteralValue::Tuple(values) => {
+                let mut tuple = serializer.serialize_tuple(values.len())?;
+                for value in values {
+                    tuple.serialize_element(value)?;
+                }
+                tuple.end()
+            },
+            LiteralValue::Vec(values) => values.serialize(serializer),
+            LiteralValue::Option(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl LiteralValue {
+    /// Whether both values are encoded as the same Rust type, comparing the elements of nested values
+    fn has_same_type(&self, other: &LiteralValue) -> bool {
+        match (self, other) {
+            (LiteralValue::SubstateId(a), LiteralValue::SubstateId(b)) => mem::discriminant(a) == mem::discriminant(b),
+            (LiteralValue::Tuple(a), LiteralValue::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.has_same_type(b))
+            },
+            // the elements of each vec already have the same type, and an empty vec matches any other
+            (LiteralValue::Vec(a), LiteralValue::Vec(b)) => match (a.first(), b.first()) {
+                (Some(a), Some(b)) => a.has_same_type(b),
+                _ => true,
+            },
+            (LiteralValue::Option(a), LiteralValue::Option(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.has_same_type(b),
+                _ => true,
+            },
+            (a, b) => mem::discriminant(a) == mem::discriminant(b),
+        }
+    }
+}
+
+// Templates take the specific address types, not the SubstateId enum
+fn serialize_substate_id<S: Serializer>(substate_id: &SubstateId, serializer: S) -> Result<S::Ok, S::Error> {
+    match substate_id {
+        SubstateId::Component(address) => address.serialize(serializer),
+        SubstateId::Resource(address) => address.serialize(serializer),
+        SubstateId::Vault(address) => address.serialize(serializer),
+        SubstateId::NonFungible(address) => address.serialize(serializer),
+        other => other.serialize(serializer),
+    }
+}
+
+pub fn encode_typed_args(args: Vec<TypedArg>) -> Result<Vec<Arg>, WalletLibError> {
+    args.into_iter()
+        .enumerate()
+        .map(|(i, arg)| encode_typed_arg(arg, &format!("args[{}]", i)))
+        .collect()
+}
+
+pub fn encode_typed_arg(arg: TypedArg, path: &str) -> Result<Arg, WalletLibError> {
+    match arg {
+        TypedArg::Workspace(key) => {
+            if key.trim().is_empty() {
+                return Err(WalletLibError::invalid_argument(path, "the workspace key cannot be empty"));
+            }
+            Ok(Arg::Workspace(key.into_bytes()))
+        },
+        arg => {
+            let value = parse_literal(arg, path)?;
+            let bytes = tari_bor::encode(&value).map_err(WalletLibError::encoding_failed)?;
+            Ok(Arg::Literal(bytes))
+        },
+    }
+}
+
+fn parse_literal(arg: TypedArg, path: &str) -> Result<LiteralValue, WalletLibError> {
+    let invalid = |e: String| WalletLibError::invalid_argument(path, e);
+    let value = match arg {
+        TypedArg::Amount(v) => LiteralValue::Amount(Amount::new(v)),
+        TypedArg::ComponentAddress(s) => {
+            LiteralValue::ComponentAddress(ComponentAddress::from_str(&s).map_err(|e| invalid(e.to_string()))?)
+        },
+        TypedArg::ResourceAddress(s) => {
+            LiteralValue::ResourceAddress(ResourceAddress::from_str(&s).map_err(|e| invalid(e.to_string()))?)
+        },
+        TypedArg::VaultId(s) => LiteralValue::VaultId(VaultId::from_str(&s).map_err(|e| invalid(e.to_string()))?),
+        TypedArg::NonFungibleAddress(s) => {
+            let (resource_address, id) = s
+                .split_once(' ')
+                .ok_or_else(|| invalid("expected a resource address and an id separated by a space".to_string()))?;
+            let resource_address = ResourceAddress::from_str(resource_address).map_err(|e| invalid(e.to_string()))?;
+            let id = parse_non_fungible_id(id, path)?;
+            LiteralValue::NonFungibleAddress(NonFungibleAddress::new(resource_address, id))
+        },
+        TypedArg::TemplateAddress(s) => LiteralValue::TemplateAddress(
+            TemplateAddress::from_hex(&s).map_err(|e| invalid(format!("{:?}", e)))?,
+        ),
+        TypedArg::SubstateId(s) => {
+            LiteralValue::SubstateId(SubstateId::from_str(&s).map_err(|e| invalid(e.to_string()))?)
+        },
+        TypedArg::PublicKey(s) => {
+            let public_key = RistrettoPublicKey::from_hex(&s).map_err(|e| invalid(format!("{:?}", e)))?;
+            LiteralValue::PublicKey(
+                RistrettoPublicKeyBytes::from_bytes(public_key.as_bytes()).map_err(|e| invalid(format!("{:?}", e)))?,
+            )
+        },
+        TypedArg::NonFungibleId(s) => LiteralValue::NonFungibleId(parse_non_fungible_id(&s, path)?),
+        TypedArg::String(v) => LiteralValue::String(v),
+        TypedArg::Bool(v) => LiteralValue::Bool(v),
+        TypedArg::U8(v) => LiteralValue::U8(v),
+        TypedArg::U16(v) => LiteralValue::U16(v),
+        TypedArg::U32(v) => LiteralValue::U32(v),
+        TypedArg::U64(v) => LiteralValue::U64(v),
+        TypedArg::I8(v) => LiteralValue::I8(v),
+        TypedArg::I16(v) => LiteralValue::I16(v),
+        TypedArg::I32(v) => LiteralValue::I32(v),
+        TypedArg::I64(v) => LiteralValue::I64(v),
+        TypedArg::Bytes(v) => LiteralValue::Bytes(v),
+        TypedArg::Metadata(fields) => LiteralValue::Metadata(metadata_from_fields(fields)),
+        TypedArg::Workspace(_) => {
+            return Err(invalid(
+                "workspace references can only be top level arguments".to_string(),
+            ))
+        },
+        TypedArg::Tuple(args) => LiteralValue::Tuple(parse_literals(args, path)?),
+        TypedArg::Vec(args) => {
+            let values = parse_literals(args, path)?;
+            if let Some(first) = values.first() {
+                if values.iter().any(|v| !first.has_same_type(v)) {
+                    return Err(invalid("all the elements of a vec must have the same type".to_string()));
+                }
+            }
+            LiteralValue::Vec(values)
+        },
+        TypedArg::Option(arg) => LiteralValue::Option(
+            arg.map(|arg| parse_literal(*arg, &format!("{}.value", path)).map(Box::new))
+                .transpose()?,
+        ),
+    };
+    Ok(value)
+}
+
+fn parse_literals(args: Vec<TypedArg>, path: &str) -> Result<Vec<LiteralValue>, WalletLibError> {
+    args.into_iter()
+        .enumerate()
+        .map(|(i, arg)| parse_literal(arg, &format!("{}[{}]", path, i)))
+        .collect()
+}
+
+fn parse_non_fungible_id(id: &str, path: &str) -> Result<NonFungibleId, WalletLibError> {
+    NonFungibleId::try_from_canonical_string(id)
+        .map_err(|e| WalletLibError::invalid_argument(path, format!("invalid non fungible id: {:?}", e)))
+}
@@ -0,0 +1,180 @@
+//! Transfers to many recipients in a single transaction, paying a single fee.
+//! Each resource is withdrawn once and the bucket is split between the recipients by `SPLIT_FUNCTION` of the template
+//! in `templates/bucket_split`, as the account template has no method that splits a bucket.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{Amount, ComponentAddress, ResourceAddress};
+
+use crate::component::account_address_from_public_key;
+use crate::error::WalletLibError;
+
+pub const SPLIT_FUNCTION: &str = "split_and_deposit";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferItem {
+    pub destination_public_key: String,
+    pub resource_address: String,
+    pub amount: i64,
+    /// Whether the destination account must be created by the transaction
+    #[serde(default)]
+    pub create_account: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Recipient {
+    public_key: RistrettoPublicKey,
+    account_address: ComponentAddress,
+    create_account: bool,
+    amounts: Vec<(ResourceAddress, i64)>,
+}
+
+/// Transfers grouped by recipient, with the amounts sent to the same recipient in the same resource merged
+#[derive(Debug, Clone)]
+pub struct BatchTransfer {
+    recipients: Vec<Recipient>,
+    totals: Vec<(ResourceAddress, i64)>,
+}
+
+impl BatchTransfer {
+    pub fn new(source_public_key: &RistrettoPublicKey, items: Vec<TransferItem>) -> Result<Self, WalletLibError> {
+        if items.is_empty() {
+            return Err(WalletLibError::invalid_argument("transfers", "there must be at least one transfer"));
+        }
+
+        let mut batch = Self {
+            recipients: vec![],
+            totals: vec![],
+        };
+        for (i, item) in items.into_iter().enumerate() {
+            let path = |field: &str| format!("transfers[{}].{}", i, field);
+            if item.amount <= 0 {
+                return Err(WalletLibError::invalid_argument(&path("amount"), "the amount must be positive"));
+            }
+            let public_key = RistrettoPublicKey::from_hex(&item.destination_public_key)
+                .map_err(|e| WalletLibError::invalid_argument(&path("destination_public_key"), format!("{:?}", e)))?;
+            if public_key == *source_public_key {
+                return Err(WalletLibError::invalid_argument(
+                    &path("destination_public_key"),
+                    "cannot transfer to the source account",
+                ));
+            }
+            let resource_address = ResourceAddress::from_str(&item.resource_address)
+                .map_err(|e| WalletLibError::invalid_argument(&path("resource_address"), e))?;
+
+            let recipient = batch.recipient_mut(public_key);
+            recipient.create_account |= item.create_account;
+            add_amount(&mut recipient.amounts, resource_address, item.amount, &path("amount"))?;
+            add_amount(&mut batch.totals, resource_address, item.amount, &path("amount"))?;
+        }
+        Ok(batch)
+    }
+
+    /// Total amount sent of each resource, in the order the resources first appear
+    pub fn resource_totals(&self) -> &[(ResourceAddress, i64)] {
+        &self.totals
+    }
+
+    /// Checks that `balances` cover the totals plus the `fee`, which is paid in XTR.
+    /// Resources missing from `balances` have no funds.
+    pub fn check_balances(&self, balances: &HashMap<ResourceAddress, i64>, fee: i64) -> Result<(), WalletLibError> {
+        let mut totals = self.totals.clone();
+        add_amount(&mut totals, XTR, fee, "fee")?;
+        for (resource_address, total) in &totals {
+            let available = balances.get(resource_address).copied().unwrap_or(0);
+            if *total > available {
+                return Err(WalletLibError::insufficient_funds(*total as u64, available.max(0) as u64));
+            }
+        }
+        Ok(())
+    }
+
+    /// Instructions that move the funds from the account at `source_account_address` to every recipient, with the
+    /// bucket split template published at `split_template_address`
+    pub fn instructions(
+        &self,
+        source_account_address: ComponentAddress,
+        split_template_address: TemplateAddress,
+    ) -> Vec<Instruction> {
+        let mut instructions = self
+            .recipients
+            .iter()
+            .filter(|recipient| recipient.create_account)
+            .map(|recipient| Instruction::CreateAccount {
+                owner_public_key: recipient.public_key.clone(),
+                workspace_bucket: None,
+            })
+            .collect::<Vec<_>>();
+
+        for (i, (resource_address, total)) in self.totals.iter().enumerate() {
+            let bucket = format!("bucket_{}", i);
+            let deposits = self
+                .recipients
+                .iter()
+                .filter_map(|recipient| {
+                    let (_, amount) = recipient.amounts.iter().find(|(r, _)| r == resource_address)?;
+                    Some((recipient.account_address, Amount::new(*amount)))
+                })
+                .collect::<Vec<_>>();
+            let mut args = vec![Arg::Workspace(bucket.clone().into_bytes())];
+            args.extend(args![deposits]);
+
+            instructions.push(Instruction::CallMethod {
+                component_address: source_account_address,
+                method: "withdraw".to_string(),
+                args: args![*resource_address, Amount::new(*total)],
+            });
+            instructions.push(Instruction::PutLastInstructionOutputOnWorkspace {
+                key: bucket.into_bytes(),
+            });
+            instructions.push(Instruction::CallFunction {
+                template_address: split_template_address,
+                function: SPLIT_FUNCTION.to_string(),
+                args,
+            });
+        }
+        instructions
+    }
+
+    fn recipient_mut(&mut self, public_key: RistrettoPublicKey) -> &mut Recipient {
+        let position = match self.recipients.iter().position(|r| r.public_key == public_key) {
+            Some(position) => position,
+            None => {
+                self.recipients.push(Recipient {
+                    account_address: account_address_from_public_key(&public_key),
+                    public_key,
+                    create_account: false,
+                    amounts: vec![],
+                });
+                self.recipients.len() - 1
+            },
+        };
+        &mut self.recipients[position]
+    }
+}
+
+fn add_amount(
+    amounts: &mut Vec<(ResourceAddress, i64)>,
+    resource_address: ResourceAddress,
+    amount: i64,
+    path: &str,
+) -> Result<(), WalletLibError> {
+    match amounts.iter_mut().find(|(r, _)| *r == resource_address) {
+        Some((_, total)) => {
+            *total = total
+                .checked_add(amount)
+                .ok_or_else(|| WalletLibError::invalid_argument(path, "the total amount overflows"))?;
+        },
+        None => amounts.push((resource_address, amount)),
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Tari CipherSeed (aezeed style) mnemonics, as created by the Tari console wallet and the DAN wallet daemon.
+//! The 24 word mnemonic encodes `version || encrypt(birthday || entropy || mac) || salt || checksum`.
+
+use std::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use bip39::Language;
+use blake2::Blake2b;
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use chacha20::{ChaCha20, Key, Nonce};
+use digest::consts::U32;
+use subtle::ConstantTimeEq;
+use tari_crypto::hashing::DomainSeparatedHasher;
+use zeroize::Zeroizing;
+
+use crate::key_manager::KeyManagerDomain;
+
+const LABEL_ARGON_ENCODING: &str = "argon2_encoding";
+const LABEL_CHACHA20_ENCODING: &str = "chacha20_encoding";
+const LABEL_MAC_GENERATION: &str = "mac_generation";
+
+const CIPHER_SEED_VERSION: u8 = 2;
+pub const DEFAULT_CIPHER_SEED_PASSPHRASE: &str = "TARI_CIPHER_SEED";
+pub const MNEMONIC_WORD_COUNT: usize = 24;
+
+const BITS_PER_WORD: usize = 11;
+const CIPHER_SEED_BIRTHDAY_BYTES: usize = 2;
+const CIPHER_SEED_ENTROPY_BYTES: usize = 16;
+const CIPHER_SEED_MAIN_SALT_BYTES: usize = 5;
+const ARGON2_SALT_BYTES: usize = 16;
+const CIPHER_SEED_MAC_BYTES: usize = 5;
+const CIPHER_SEED_ENCRYPTION_KEY_BYTES: usize = 32;
+const CIPHER_SEED_MAC_KEY_BYTES: usize = 32;
+const CIPHER_SEED_CHECKSUM_BYTES: usize = 4;
+const CIPHER_SEED_SECRET_BYTES: usize = CIPHER_SEED_BIRTHDAY_BYTES + CIPHER_SEED_ENTROPY_BYTES + CIPHER_SEED_MAC_BYTES;
+const CIPHER_SEED_BYTES: usize =
+    1 + CIPHER_SEED_SECRET_BYTES + CIPHER_SEED_MAIN_SALT_BYTES + CIPHER_SEED_CHECKSUM_BYTES;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CipherSeedError {
+    InvalidWordCount(usize),
+    UnknownWord(String),
+    UnsupportedVersion(u8),
+    ChecksumMismatch,
+    DecryptionFailed,
+    KeyDerivation(String),
+}
+
+impl fmt::Display for CipherSeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CipherSeedError::InvalidWordCount(count) => {
+                write!(f, "Expected {} seed words but got {}", MNEMONIC_WORD_COUNT, count)
+            },
+            CipherSeedError::UnknownWord(word) => write!(f, "'{}' is not a valid seed word", word),
+            CipherSeedError::UnsupportedVersion(version) => write!(f, "Unsupported cipher seed version {}", version),
+            CipherSeedError::ChecksumMismatch => write!(f, "The seed words checksum does not match"),
+            CipherSeedError::DecryptionFailed => {
+                write!(f, "Could not decrypt the cipher seed, the passphrase is probably wrong")
+            },
+            CipherSeedError::KeyDerivation(reason) => write!(f, "Could not derive key: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CipherSeedError {}
+
+pub struct CipherSeed {
+    version: u8,
+    birthday: u16,
+    entropy: Zeroizing<[u8; CIPHER_SEED_ENTROPY_BYTES]>,
+}
+
+impl CipherSeed {
+    /// Parses and decrypts a 24 word English mnemonic.
+    /// Wallets that were not given a passphrase use `DEFAULT_CIPHER_SEED_PASSPHRASE`.
+    pub fn from_mnemonic(mnemonic: &str, passphrase: Option<&str>) -> Result<Self, CipherSeedError> {
+        let bytes = mnemonic_to_bytes(mnemonic)?;
+        Self::from_enciphered_bytes(&bytes, passphrase.unwrap_or(DEFAULT_CIPHER_SEED_PASSPHRASE))
+    }
+
+    fn from_enciphered_bytes(encrypted_seed: &[u8], passphrase: &str) -> Result<Self, CipherSeedError> {
+        if encrypted_seed.len() != CIPHER_SEED_BYTES {
+            return Err(CipherSeedError::InvalidWordCount(
+                encrypted_seed.len() * 8 / BITS_PER_WORD,
+            ));
+        }
+
+        let version = encrypted_seed[0];
+        if version != CIPHER_SEED_VERSION {
+            return Err(CipherSeedError::UnsupportedVersion(version));
+        }
+
+        // Verify the checksum first, to detect obvious typos before running the expensive key derivation
+        let (seed, checksum) = encrypted_seed.split_at(CIPHER_SEED_BYTES - CIPHER_SEED_CHECKSUM_BYTES);
+        let mut crc_hasher = crc32fast::Hasher::new();
+        crc_hasher.update(seed);
+        if crc_hasher.finalize().to_le_bytes() != checksum {
+            return Err(CipherSeedError::ChecksumMismatch);
+        }
+
+        let (secret_data, salt) = seed[1..].split_at(CIPHER_SEED_SECRET_BYTES);
+        let (encryption_key, mac_key) = derive_cipher_keys(passphrase, salt)?;
+
+        let mut secret_data = Zeroizing::new(secret_data.to_vec());
+        apply_stream_cipher(&mut secret_data, &encryption_key, salt);
+
+        let (birthday_bytes, rest) = secret_data.split_at(CIPHER_SEED_BIRTHDAY_BYTES);
+        let (entropy, mac) = rest.split_at(CIPHER_SEED_ENTROPY_BYTES);
+
+        let expected_mac = generate_mac(birthday_bytes, entropy, version, salt, &mac_key);
+        if mac.ct_eq(&expected_mac).unwrap_u8() == 0 {
+            return Err(CipherSeedError::DecryptionFailed);
+        }
+
+        let mut seed_entropy = Zeroizing::new([0u8; CIPHER_SEED_ENTROPY_BYTES]);
+        seed_entropy.copy_from_slice(entropy);
+
+        Ok(Self {
+            version,
+            birthday: u16::from_le_bytes([birthday_bytes[0], birthday_bytes[1]]),
+            entropy: seed_entropy,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Days since the Tari birthday genesis (2022-01-01) when the seed was created
+    pub fn birthday(&self) -> u16 {
+        self.birthday
+    }
+
+    pub fn entropy(&self) -> &[u8] {
+        self.entropy.as_ref()
+    }
+}
+
+fn mnemonic_to_bytes(mnemonic: &str) -> Result<Zeroizing<Vec<u8>>, CipherSeedError> {
+    let words: Vec<String> = mnemonic.split_whitespace().map(|w| w.to_lowercase()).collect();
+    if words.len() != MNEMONIC_WORD_COUNT {
+        return Err(CipherSeedError::InvalidWordCount(words.len()));
+    }
+
+    // Each word holds 11 bits of the seed, packed least significant bit first
+    let mut bytes = Zeroizing::new(Vec::with_capacity(CIPHER_SEED_BYTES));
+    let mut buffer = 0u32;
+    let mut buffered_bits = 0;
+    for word in &words {
+        let index = Language::English
+            .find_word(word)
+            .ok_or_else(|| CipherSeedError::UnknownWord(word.clone()))?;
+        buffer |= u32::from(index) << buffered_bits;
+        buffered_bits += BITS_PER_WORD;
+        while buffered_bits >= 8 {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            buffered_bits -= 8;
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn derive_cipher_keys(
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<(Zeroizing<Vec<u8>>, Zeroizing<Vec<u8>>), CipherSeedError> {
+    // The Argon2 salt is derived from the main salt
+    let argon2_salt = DomainSeparatedHasher::<Blake2b<U32>, KeyManagerDomain>::new_with_label(LABEL_ARGON_ENCODING)
+        .chain(salt)
+        .finalize();
+
+    // A single Argon2 run produces both keys
+    let params = Params::new(
+        46 * 1024,
+        1,
+        1,
+        Some(CIPHER_SEED_ENCRYPTION_KEY_BYTES + CIPHER_SEED_MAC_KEY_BYTES),
+    )
+    .map_err(|e| CipherSeedError::KeyDerivation(e.to_string()))?;
+    let mut main_key = Zeroizing::new(vec![0u8; CIPHER_SEED_ENCRYPTION_KEY_BYTES + CIPHER_SEED_MAC_KEY_BYTES]);
+    Argon2::new(Algorithm::Argon2d, Version::V0x13, params)
+        .hash_password_into(
+            passphrase.as_bytes(),
+            &argon2_salt.as_ref()[..ARGON2_SALT_BYTES],
+            &mut main_key,
+        )
+        .map_err(|e| CipherSeedError::KeyDerivation(e.to_string()))?;
+
+    let mac_key = Zeroizing::new(main_key[CIPHER_SEED_ENCRYPTION_KEY_BYTES..].to_vec());
+    main_key.truncate(CIPHER_SEED_ENCRYPTION_KEY_BYTES);
+    Ok((main_key, mac_key))
+}
+
+fn apply_stream_cipher(data: &mut [u8], encryption_key: &[u8], salt: &[u8]) {
+    // The ChaCha20 nonce is derived from the main salt
+    let nonce = DomainSeparatedHasher::<Blake2b<U32>, KeyManagerDomain>::new_with_label(LABEL_CHACHA20_ENCODING)
+        .chain(salt)
+        .finalize();
+    let nonce = &nonce.as_ref()[..std::mem::size_of::<Nonce>()];
+    let mut cipher = ChaCha20::new(Key::from_slice(encryption_key), Nonce::from_slice(nonce));
+    cipher.apply_keystream(data);
+}
+
+fn generate_mac(birthday: &[u8], entropy: &[u8], version: u8, salt: &[u8], mac_key: &[u8]) -> Vec<u8> {
+    DomainSeparatedHasher::<Blake2b<U32>, KeyManagerDomain>::new_with_label(LABEL_MAC_GENERATION)
+        .chain(birthday)
+        .chain(entropy)
+        .chain([version])
+        .chain(salt)
+        .chain(mac_key)
+        .finalize()
+        .as_ref()[..CIPHER_SEED_MAC_BYTES]
+        .to_vec()
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+
+use crate::confidential_transfer::{ConfidentialOutputModel, OutputStatus};
+
+/// How confidential outputs are picked to cover a spend amount
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub enum CoinSelectionStrategy {
+    /// Spend the largest outputs first, keeping the number of inputs low
+    #[default]
+    LargestFirst,
+    /// Spend the smallest outputs first, consolidating dust into the change output
+    SmallestFirst,
+    /// Pick the outputs whose total is closest to the amount, keeping the change output small
+    MinimizeChange,
+    /// Pick outputs in random order, so the inputs of a transaction say nothing about the rest of the vault
+    Random,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct InsufficientFundsError {
+    pub requested: u64,
+    pub available: u64,
+    pub shortfall: u64,
+}
+
+impl fmt::Display for InsufficientFundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Insufficient funds: requested {} but only {} is available (short by {})",
+            self.requested, self.available, self.shortfall
+        )
+    }
+}
+
+impl std::error::Error for InsufficientFundsError {}
+
+/// Selects unspent outputs covering `amount` following `strategy`.
+/// Returns the selected outputs together with their total value.
+pub fn select_outputs(
+    outputs: Vec<ConfidentialOutputModel>,
+    amount: u64,
+    strategy: CoinSelectionStrategy,
+) -> Result<(Vec<ConfidentialOutputModel>, u64), InsufficientFundsError> {
+    let mut outputs: Vec<ConfidentialOutputModel> = outputs
+        .into_iter()
+        .filter(|o| matches!(o.status, OutputStatus::Unspent))
+        .collect();
+
+    let available = outputs.iter().map(|o| o.value).sum::<u64>();
+    if available < amount {
+        return Err(InsufficientFundsError {
+            requested: amount,
+            available,
+            shortfall: amount - available,
+        });
+    }
+    if amount == 0 {
+        return Ok((vec![], 0));
+    }
+
+    let selected = match strategy {
+        CoinSelectionStrategy::LargestFirst => {
+            outputs.sort_by(|a, b| b.value.cmp(&a.value));
+            take_until_covered(outputs, amount)
+        },
+        CoinSelectionStrategy::SmallestFirst => {
+            outputs.sort_by(|a, b| a.value.cmp(&b.value));
+            take_until_covered(outputs, amount)
+        },
+        CoinSelectionStrategy::MinimizeChange => select_minimizing_change(outputs, amount),
+        CoinSelectionStrategy::Random => {
+            outputs.shuffle(&mut rand::thread_rng());
+            take_until_covered(outputs, amount)
+        },
+    };
+
+    let total = selected.iter().map(|o| o.value).sum();
+    Ok((selected, total))
+}
+
+fn take_until_covered(outputs: Vec<ConfidentialOutputModel>, amount: u64) -> Vec<ConfidentialOutputModel> {
+    let mut total = 0;
+    outputs
+        .into_iter()
+        .take_while(|o| {
+            let needed = total < amount;
+            total += o.value;
+            needed
+        })
+        .collect()
+}
+
+/// Upper bound of the selections tried when minimizing the change. Past it the best selection found so far is used,
+/// which is never worse than spending the largest outputs first.
+const MINIMIZE_CHANGE_MAX_TRIES: usize = 100_000;
+
+fn select_minimizing_change(mut outputs: Vec<ConfidentialOutputModel>, amount: u64) -> Vec<ConfidentialOutputModel> {
+    // Largest first, so good selections are found early and more branches are pruned
+    outputs.sort_by(|a, b| b.value.cmp(&a.value));
+    let values: Vec<u64> = outputs.iter().map(|o| o.value).collect();
+
+    let mut search = ChangeSearch::new(&values, amount);
+    search.search(0, 0);
+
+    let selected = search.best;
+    outputs
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected.contains(i))
+        .map(|(_, o)| o)
+        .collect()
+}
+
+/// Branch and bound search of the selection with the smallest total that covers the amount
+struct ChangeSearch<'a> {
+    values: &'a [u64],
+    /// Total of the values from each index on, to prune the branches that cannot cover the amount anymore
+    remaining: Vec<u64>,
+    amount: u64,
+    current: Vec<usize>,
+    best: Vec<usize>,
+    best_total: u64,
+    tries: usize,
+}
+
+impl<'a> ChangeSearch<'a> {
+    fn new(values: &'a [u64], amount: u64) -> Self {
+        let mut remaining = vec![0; values.len() + 1];
+        for i in (0..values.len()).rev() {
+            remaining[i] = remaining[i + 1] + values[i];
+        }
+
+        // Start from the largest first selection, so running out of tries still gives a valid selection
+        let mut best = vec![];
+        let mut best_total = 0;
+        for (i, value) in values.iter().enumerate() {
+            if best_total >= amount {
+                break;
+            }
+            best.push(i);
+            best_total += value;
+        }
+
+        Self {
+            values,
+            remaining,
+            amount,
+            current: vec![],
+            best,
+            best_total,
+            tries: 0,
+        }
+    }
+
+    fn search(&mut self, index: usize, total: u64) {
+        if total >= self.amount {
+            if total < self.best_total {
+                self.best_total = total;
+                self.best = self.current.clone();
+            }
+            return;
+        }
+        if self.best_total == self.amount || self.tries >= MINIMIZE_CHANGE_MAX_TRIES {
+            return;
+        }
+        if index == self.values.len() || total + self.remaining[index] < self.amount {
+            return;
+        }
+        self.tries += 1;
+
+        // Adding outputs only increases the total, so a branch already at the best total cannot improve it
+        let with_output = total + self.values[index];
+        if with_output < self.best_total {
+            self.current.push(index);
+            self.search(index + 1, with_output);
+            self.current.pop();
+        }
+        self.search(index + 1, total);
+    }
+}
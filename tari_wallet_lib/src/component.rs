@@ -1,12 +1,30 @@
 use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_crypto::tari_utilities::hex::Hex;
+use tari_crypto::tari_utilities::ByteArray;
 use tari_engine_types::component::new_component_address_from_public_key;
-use tari_template_builtin::ACCOUNT_TEMPLATE_ADDRESS;
-use tari_template_lib::prelude::ComponentAddress;
-use wasm_bindgen::JsError;
-
-pub fn get_account_address_from_public_key(public_key: &str) -> Result<ComponentAddress, JsError> {
-    let destination_component_id = RistrettoPublicKey::from_hex(public_key).unwrap();
-    let account_address = new_component_address_from_public_key(&ACCOUNT_TEMPLATE_ADDRESS, &destination_component_id);
-    Ok(account_address)
-}
\ No newline at end of file
+use tari_template_builtin::{ACCOUNT_NFT_TEMPLATE_ADDRESS, ACCOUNT_TEMPLATE_ADDRESS};
+use tari_template_lib::prelude::{ComponentAddress, NonFungibleAddress, RistrettoPublicKeyBytes};
+
+use crate::error::WalletLibError;
+
+pub fn get_account_address_from_public_key(public_key: &str) -> Result<ComponentAddress, WalletLibError> {
+    let destination_component_id =
+        RistrettoPublicKey::from_hex(public_key).map_err(WalletLibError::invalid_public_key)?;
+    Ok(account_address_from_public_key(&destination_component_id))
+}
+
+pub fn account_address_from_public_key(public_key: &RistrettoPublicKey) -> ComponentAddress {
+    new_component_address_from_public_key(&ACCOUNT_TEMPLATE_ADDRESS, public_key)
+}
+
+/// Address of the account NFT component of the owner of `public_key`, which is the same on every network
+pub fn account_nft_address_from_public_key(public_key: &RistrettoPublicKey) -> ComponentAddress {
+    new_component_address_from_public_key(&ACCOUNT_NFT_TEMPLATE_ADDRESS, public_key)
+}
+
+/// Badge proving the ownership of `public_key`, used as the owner of components
+pub fn owner_token_from_public_key(public_key: &RistrettoPublicKey) -> Result<NonFungibleAddress, WalletLibError> {
+    let public_key_bytes =
+        RistrettoPublicKeyBytes::from_bytes(public_key.as_bytes()).map_err(WalletLibError::invalid_public_key)?;
+    Ok(NonFungibleAddress::from_public_key(public_key_bytes))
+}
@@ -1,16 +1,33 @@
+use std::cmp;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
+use rand::Rng;
 use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
 use tari_dan_wallet_crypto::{create_withdraw_proof, encrypt_value_and_mask, extract_value_and_mask, kdfs, unblind_output, ConfidentialOutputMaskAndValue, ConfidentialProofStatement};
 use tari_engine_types::{confidential::ConfidentialOutput, resource::Resource, substate::SubstateId, vault::Vault};
 use tari_template_lib::{args, models::{Amount, ComponentAddress, EncryptedData, ObjectKey, ResourceAddress, VaultId}};
 use tari_transaction::{Instruction, SubstateRequirement, Transaction};
-use wasm_bindgen::{JsError, JsValue};
-use tari_common_types::types::{Commitment, PrivateKey, PublicKey};
+use tari_common_types::types::{Commitment, CommitmentFactory, PrivateKey, PublicKey};
+use tari_crypto::commitment::HomomorphicCommitmentFactory;
 use tari_crypto::keys::PublicKey as _;
+use tari_crypto::tari_utilities::hex::Hex;
+
+use crate::coin_selection::{self, CoinSelectionStrategy};
+use crate::crypto;
+use crate::epoch_window::EpochWindow;
+use crate::error::WalletLibError;
 
 pub type ConfidentialProofId = u64;
 
+/// Key index reserved for the account key itself.
+/// Senders always encrypt outputs to the account public key, so the mask of an output without a stored key index is
+/// recovered by decrypting it.
+pub const ACCOUNT_KEY_INDEX: u64 = 0;
+
+/// Key index of the change outputs of an account by hex encoded commitment, as stored by the snap
+pub type OutputKeyIndexes = HashMap<String, u64>;
+
 #[derive(Debug)]
 struct InputsToSpend {
     pub confidential: Vec<ConfidentialOutputMaskAndValue>,
@@ -19,13 +36,14 @@ struct InputsToSpend {
 }
 
 impl InputsToSpend {
-    pub fn _total_amount(&self) -> Amount {
-        self.total_confidential_amount() + self.revealed
+    pub fn _total_amount(&self) -> Result<Amount, WalletLibError> {
+        Ok(self.total_confidential_amount()? + self.revealed)
     }
 
-    pub fn total_confidential_amount(&self) -> Amount {
+    pub fn total_confidential_amount(&self) -> Result<Amount, WalletLibError> {
         let confidential_amt = self.confidential.iter().map(|o| o.value).sum::<u64>();
-        Amount::try_from(confidential_amt).unwrap()
+        Amount::try_from(confidential_amt)
+            .map_err(|_| WalletLibError::confidential_proof_failed("Confidential balance overflows the amount type"))
     }
 }
 
@@ -44,6 +62,8 @@ pub struct ConfidentialOutputModel {
     pub commitment: Commitment,
     pub value: u64,
     pub sender_public_nonce: Option<PublicKey>,
+    /// Index of the key the mask of the output was derived from, or `ACCOUNT_KEY_INDEX` when the mask is recovered by
+    /// decrypting `encrypted_data` with the account key, as for outputs received from other accounts
     pub encryption_secret_key_index: u64,
     pub encrypted_data: EncryptedData,
     pub public_asset_tag: Option<PublicKey>,
@@ -85,6 +105,10 @@ pub struct ConfidentialTransferParams {
     pub proof_from_resource: Option<ResourceAddress>,
     pub output_to_revealed: bool,
     pub input_selection: ConfidentialTransferInputSelection,
+    pub coin_selection: CoinSelectionStrategy,
+    /// Key indexes of the change outputs previously sent back to the source account
+    pub output_key_indexes: OutputKeyIndexes,
+    pub epoch_window: EpochWindow,
 }
 
 impl ConfidentialTransferParams {
@@ -96,7 +120,7 @@ impl ConfidentialTransferParams {
         }
     }
 
-    pub fn _revealed_amount(&self) -> Amount {
+    pub fn revealed_amount(&self) -> Amount {
         if self.output_to_revealed {
             Amount::new(self.amount)
         } else {
@@ -105,12 +129,60 @@ impl ConfidentialTransferParams {
     }
 }
 
+/// A confidential transfer along with the change output it sends back to the source account, so the index of the key
+/// used for its mask can be stored
+#[derive(Debug, Clone)]
+pub struct ConfidentialTransfer {
+    pub transaction: Transaction,
+    pub change_output: Option<ConfidentialOutputModel>,
+}
+
+/// Key index of an output in the form the snap stores it. The index is a decimal string because most indexes do not
+/// fit in a JS number.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct OutputKeyIndex {
+    pub commitment: String,
+    pub key_index: String,
+}
+
+impl OutputKeyIndex {
+    pub fn new(output: &ConfidentialOutputModel) -> Self {
+        Self {
+            commitment: commitment_hex(&output.commitment),
+            key_index: output.encryption_secret_key_index.to_string(),
+        }
+    }
+}
+
+/// Parses the key indexes stored by the snap, a map from hex encoded commitment to decimal key index
+pub fn parse_output_key_indexes(indexes: HashMap<String, String>) -> Result<OutputKeyIndexes, WalletLibError> {
+    indexes
+        .into_iter()
+        .map(|(commitment, key_index)| {
+            let key_index = key_index.parse::<u64>().map_err(|e| {
+                WalletLibError::invalid_argument(&format!("output_key_indexes.{}", commitment), e)
+            })?;
+            Ok((commitment, key_index))
+        })
+        .collect()
+}
+
+fn commitment_hex(commitment: &Commitment) -> String {
+    commitment.as_public_key().to_hex()
+}
+
 pub fn get_confidential_balance(
     vault: &Vault,
     key: &RistrettoSecretKey
-) -> Result<u64, JsError> {
+) -> Result<u64, WalletLibError> {
     let dummy_substate_id = SubstateId::Resource(ResourceAddress::new(ObjectKey::default()));
-    let vault_outputs = get_confidential_outputs_from_vault(dummy_substate_id.clone(), dummy_substate_id, vault, key)?;
+    let vault_outputs = get_confidential_outputs_from_vault(
+        dummy_substate_id.clone(),
+        dummy_substate_id,
+        vault,
+        key,
+        &OutputKeyIndexes::new(),
+    )?;
     let balance = vault_outputs.iter().map(|o| o.value).sum();
     Ok(balance)
 }
@@ -123,67 +195,127 @@ fn resolved_inputs_for_transfer(
     _resource_address: ResourceAddress,
     spend_amount: Amount,
     input_selection: ConfidentialTransferInputSelection,
-) -> Result<InputsToSpend, JsError> {
-    // TODO: should we implement other types of input selection?
-    match &input_selection {
+    coin_selection: CoinSelectionStrategy,
+    key_indexes: &OutputKeyIndexes,
+) -> Result<InputsToSpend, WalletLibError> {
+    let account_address = SubstateId::Component(from_account);
+    let outputs = get_confidential_outputs_from_vault(account_address, vault_address, &src_vault, key, key_indexes)?;
+    // The revealed funds of a confidential vault are kept as the plain vault balance
+    let selected = select_inputs(outputs, src_vault.balance(), spend_amount, input_selection, coin_selection)?;
+
+    Ok(InputsToSpend {
+        confidential: resolve_output_masks(key, selected.confidential)?,
+        _proof_id: 0,
+        revealed: selected.revealed,
+    })
+}
+
+/// Confidential outputs and revealed funds picked to cover a spend amount
+#[derive(Debug, Clone)]
+pub struct SelectedInputs {
+    pub confidential: Vec<ConfidentialOutputModel>,
+    pub revealed: Amount,
+}
+
+/// Splits `spend_amount` between the confidential `outputs` and the `revealed_balance` of a vault following
+/// `input_selection`, picking the confidential outputs with `coin_selection`
+pub fn select_inputs(
+    outputs: Vec<ConfidentialOutputModel>,
+    revealed_balance: Amount,
+    spend_amount: Amount,
+    input_selection: ConfidentialTransferInputSelection,
+    coin_selection: CoinSelectionStrategy,
+) -> Result<SelectedInputs, WalletLibError> {
+    let confidential_balance = outputs
+        .iter()
+        .filter(|o| matches!(o.status, OutputStatus::Unspent))
+        .map(|o| o.value)
+        .sum::<u64>();
+    let confidential_balance = Amount::try_from(confidential_balance)
+        .map_err(|_| WalletLibError::confidential_proof_failed("Confidential balance overflows the amount type"))?;
+
+    match input_selection {
         ConfidentialTransferInputSelection::ConfidentialOnly => {
-            let (confidential_inputs, _) = get_confidential_amount_from_vault(SubstateId::Component(from_account), vault_address, &src_vault, key, spend_amount)?;
-            let confidential_inputs = resolve_output_masks(key, confidential_inputs)?;
+            let (confidential, _) = select_confidential_outputs(outputs, spend_amount, coin_selection)?;
 
-            Ok(InputsToSpend {
-                confidential: confidential_inputs,
-                _proof_id: 0,
+            Ok(SelectedInputs {
+                confidential,
                 revealed: Amount::zero(),
             })
         },
         ConfidentialTransferInputSelection::RevealedOnly => {
-            todo!()
+            if revealed_balance < spend_amount {
+                return Err(insufficient_funds(spend_amount, revealed_balance));
+            }
+
+            Ok(SelectedInputs {
+                confidential: vec![],
+                revealed: spend_amount,
+            })
         },
         ConfidentialTransferInputSelection::PreferRevealed => {
-            todo!()
+            if revealed_balance + confidential_balance < spend_amount {
+                return Err(insufficient_funds(spend_amount, revealed_balance + confidential_balance));
+            }
+            let revealed_to_spend = cmp::max(cmp::min(revealed_balance, spend_amount), Amount::zero());
+            let confidential_to_spend = spend_amount - revealed_to_spend;
+
+            let (confidential, _) = select_confidential_outputs(outputs, confidential_to_spend, coin_selection)?;
+
+            Ok(SelectedInputs {
+                confidential,
+                revealed: revealed_to_spend,
+            })
         },
         ConfidentialTransferInputSelection::PreferConfidential => {
-            todo!()
+            if revealed_balance + confidential_balance < spend_amount {
+                return Err(insufficient_funds(spend_amount, revealed_balance + confidential_balance));
+            }
+            // Spend as much as possible from the confidential outputs
+            let confidential_to_spend = cmp::max(cmp::min(confidential_balance, spend_amount), Amount::zero());
+            let (confidential, confidential_total) =
+                select_confidential_outputs(outputs, confidential_to_spend, coin_selection)?;
+
+            // Whatever the confidential outputs cannot cover is taken from the revealed funds
+            let revealed_to_spend = if confidential_total >= spend_amount {
+                Amount::zero()
+            } else {
+                spend_amount - confidential_total
+            };
+
+            Ok(SelectedInputs {
+                confidential,
+                revealed: revealed_to_spend,
+            })
         },
     }
 }
 
-fn get_confidential_amount_from_vault(
+fn select_confidential_outputs(
+    outputs: Vec<ConfidentialOutputModel>,
+    amount: Amount,
+    strategy: CoinSelectionStrategy,
+) -> Result<(Vec<ConfidentialOutputModel>, Amount), WalletLibError> {
+    let amount = amount.as_u64_checked()
+        .ok_or_else(|| WalletLibError::invalid_argument("amount", "the amount cannot be negative"))?;
+
+    let (selected, total) = coin_selection::select_outputs(outputs, amount, strategy)?;
+    let total = Amount::try_from(total)
+        .map_err(|_| WalletLibError::confidential_proof_failed("Confidential balance overflows the amount type"))?;
+    Ok((selected, total))
+}
+
+/// Decrypts the outputs of `vault`. Outputs with an entry in `key_indexes` are recorded with their key index, so their
+/// mask is derived again from it when spending them.
+fn get_confidential_outputs_from_vault(
     account_address: SubstateId,
     vault_address: SubstateId,
     vault: &Vault,
     key: &RistrettoSecretKey,
-    amount: Amount,
-) -> Result<(Vec<ConfidentialOutputModel>, u64), JsError> {
-    if amount.is_negative() {
-        return Err(JsError::new("Amount cannot be negative"));
-    }
-    let amount = amount.as_u64_checked().unwrap();
-    let mut total_output_amount = 0;
-    let mut outputs = Vec::new();
-
-    let mut vault_outputs = get_confidential_outputs_from_vault(account_address, vault_address, vault, key)?;
-
-    while total_output_amount < amount {
-        let output =
-            vault_outputs.pop();
-        match output {
-            Some(output) => {
-                total_output_amount += output.value;
-                outputs.push(output);
-            },
-            None => {
-                break;
-            }            
-        }
-    }
-
-    Ok((outputs, total_output_amount))
-}
-
-fn get_confidential_outputs_from_vault(account_address: SubstateId, vault_address: SubstateId, vault: &Vault, key: &RistrettoSecretKey) -> Result<Vec<ConfidentialOutputModel>, JsError> {
+    key_indexes: &OutputKeyIndexes,
+) -> Result<Vec<ConfidentialOutputModel>, WalletLibError> {
     let commitments = vault.get_confidential_commitments()
-        .ok_or(JsError::new(&format!("No confidential commitments in the vault")))?;
+        .ok_or(WalletLibError::NotAConfidentialVault)?;
     let outputs: Vec<ConfidentialOutput> = commitments.values().cloned().collect();
 
     // extract value from each commitment and build the ConfidentialOutputModel
@@ -209,8 +341,10 @@ fn get_confidential_outputs_from_vault(account_address: SubstateId, vault_addres
             commitment: output.commitment.clone(),
             value,
             sender_public_nonce: Some(output.stealth_public_nonce.clone()),
-            // TODO: this field is used somewhere?
-            encryption_secret_key_index: 0_u64,
+            encryption_secret_key_index: key_indexes
+                .get(&commitment_hex(&output.commitment))
+                .copied()
+                .unwrap_or(ACCOUNT_KEY_INDEX),
             encrypted_data: output.encrypted_data.clone(),
             public_asset_tag: None,
             status,
@@ -226,31 +360,10 @@ fn get_confidential_outputs_from_vault(account_address: SubstateId, vault_addres
 fn resolve_output_masks(
     account_key: &RistrettoSecretKey,
     outputs: Vec<ConfidentialOutputModel>,
-) -> Result<Vec<ConfidentialOutputMaskAndValue>, JsError> {
+) -> Result<Vec<ConfidentialOutputMaskAndValue>, WalletLibError> {
     let mut outputs_with_masks = Vec::with_capacity(outputs.len());
     for output in outputs {
-        let output_key =
-            derive_key(account_key, output.encryption_secret_key_index)?;
-        // Either derive the mask from the sender's public nonce or from the local key manager
-        let shared_decrypt_key = match output.sender_public_nonce {
-            Some(nonce) => {
-                // Derive shared secret
-                kdfs::encrypted_data_dh_kdf_aead(&output_key, &nonce)
-            },
-            None => {
-                // Derive local secret
-                let output_key = 
-                    derive_key(account_key, output.encryption_secret_key_index)?;
-                output_key
-            },
-        };
-
-        let (_, mask) = extract_value_and_mask(
-            &shared_decrypt_key,
-            &output.commitment,
-            &output.encrypted_data,
-        )?;
-
+        let mask = output_mask(account_key, &output)?;
         outputs_with_masks.push(ConfidentialOutputMaskAndValue {
             value: output.value,
             mask,
@@ -259,14 +372,65 @@ fn resolve_output_masks(
     Ok(outputs_with_masks)
 }
 
-fn derive_key(account_key: &RistrettoSecretKey, _index: u64) -> Result<RistrettoSecretKey, JsError> {
-    //TODO: this is not safe, we must implement a key derivation for the snap
-    Ok(account_key.clone())
+/// Recovers the mask of an output of the account, either by deriving it again from its key index or by decrypting it
+pub fn output_mask(
+    account_key: &RistrettoSecretKey,
+    output: &ConfidentialOutputModel,
+) -> Result<PrivateKey, WalletLibError> {
+    if output.encryption_secret_key_index != ACCOUNT_KEY_INDEX {
+        let mask = derive_key(account_key, output.encryption_secret_key_index)?;
+        if CommitmentFactory::default().commit_value(&mask, output.value) != output.commitment {
+            return Err(WalletLibError::confidential_proof_failed(
+                "The output commitment does not match the mask derived from its key index",
+            ));
+        }
+        return Ok(mask);
+    }
+
+    // Either derive the mask from the sender's public nonce or from the local key manager
+    let shared_decrypt_key = match &output.sender_public_nonce {
+        Some(nonce) => {
+            // Derive shared secret
+            kdfs::encrypted_data_dh_kdf_aead(account_key, nonce)
+        },
+        None => {
+            // Derive local secret
+            account_key.clone()
+        },
+    };
+
+    let (_, mask) = extract_value_and_mask(
+        &shared_decrypt_key,
+        &output.commitment,
+        &output.encrypted_data,
+    )
+    .map_err(WalletLibError::confidential_proof_failed)?;
+    Ok(mask)
 }
 
-pub fn next_key(account_key: &RistrettoSecretKey) -> Result<RistrettoSecretKey, JsError> {
-    //TODO: this is not safe, we must implement a key derivation for the snap
-    Ok(account_key.clone())
+fn insufficient_funds(requested: Amount, available: Amount) -> WalletLibError {
+    WalletLibError::insufficient_funds(
+        requested.as_u64_checked().unwrap_or(0),
+        available.as_u64_checked().unwrap_or(0),
+    )
+}
+
+fn derive_key(account_key: &RistrettoSecretKey, index: u64) -> Result<RistrettoSecretKey, WalletLibError> {
+    // Outputs created before key derivation existed are recorded with this index, so they remain spendable
+    if index == ACCOUNT_KEY_INDEX {
+        return Ok(account_key.clone());
+    }
+    crypto::derive_output_key(account_key, index)
+        .map_err(WalletLibError::key_derivation_failed)
+}
+
+/// Returns a fresh key index together with the key derived for it.
+pub fn next_key(account_key: &RistrettoSecretKey) -> Result<(u64, RistrettoSecretKey), WalletLibError> {
+    // The snap does not persist a key counter between calls, so a sequential index would repeat across transactions.
+    // Picking it at random over the whole non-reserved range makes a collision negligible.
+    let index = rand::thread_rng().gen_range(ACCOUNT_KEY_INDEX + 1..=u64::MAX);
+    let key = derive_key(account_key, index)?;
+    Ok((index, key))
 }
 
 fn create_confidential_proof_statement(
@@ -274,42 +438,71 @@ fn create_confidential_proof_statement(
     dest_public_key: &RistrettoPublicKey,
     confidential_amount: Amount,
     resource_view_key: Option<RistrettoPublicKey>,
-) -> Result<ConfidentialProofStatement, JsError> {
-    let mask = if confidential_amount.is_zero() {
-        PrivateKey::default()
+) -> Result<(ConfidentialProofStatement, u64), WalletLibError> {
+    let (mask_index, mask) = if confidential_amount.is_zero() {
+        (ACCOUNT_KEY_INDEX, PrivateKey::default())
     } else {
         next_key(account_key)?
     };
 
     let (nonce, public_nonce) = PublicKey::random_keypair(&mut rand::thread_rng());  
-    let encrypted_data = encrypt_value_and_mask(
-        confidential_amount
-            .as_u64_checked()
-            .unwrap_or_else(|| panic!("BUG: confidential_amount {} is negative", confidential_amount)),
-        &mask,
-        dest_public_key,
-        &nonce,
-    )?;
+    let value = confidential_amount
+        .as_u64_checked()
+        .ok_or_else(|| WalletLibError::invalid_argument("amount", "the amount cannot be negative"))?;
+    let encrypted_data = encrypt_value_and_mask(value, &mask, dest_public_key, &nonce)
+        .map_err(WalletLibError::confidential_proof_failed)?;
 
-    Ok(ConfidentialProofStatement {
+    let statement = ConfidentialProofStatement {
         amount: confidential_amount,
         mask,
         sender_public_nonce: public_nonce,
         encrypted_data,
         minimum_value_promise: 0,
         resource_view_key,
-    })
+    };
+    Ok((statement, mask_index))
+}
+
+/// Creates an output of `amount` back to the account of `account_key`, recorded with the index of its mask key
+pub fn create_change_output(
+    account_key: &RistrettoSecretKey,
+    account_address: SubstateId,
+    vault_address: SubstateId,
+    amount: Amount,
+    resource_view_key: Option<RistrettoPublicKey>,
+) -> Result<(ConfidentialProofStatement, ConfidentialOutputModel), WalletLibError> {
+    let account_public_key = RistrettoPublicKey::from_secret_key(account_key);
+    let (statement, mask_index) =
+        create_confidential_proof_statement(account_key, &account_public_key, amount, resource_view_key)?;
+    let value = amount
+        .as_u64_checked()
+        .ok_or_else(|| WalletLibError::invalid_argument("amount", "the amount cannot be negative"))?;
+
+    let output = ConfidentialOutputModel {
+        account_address,
+        vault_address,
+        commitment: CommitmentFactory::default().commit_value(&statement.mask, value),
+        value,
+        sender_public_nonce: Some(statement.sender_public_nonce.clone()),
+        encryption_secret_key_index: mask_index,
+        encrypted_data: statement.encrypted_data.clone(),
+        public_asset_tag: None,
+        status: OutputStatus::LockedUnconfirmed,
+        locked_by_proof: None,
+    };
+    Ok((statement, output))
 }
 
 pub fn build_confidential_transfer_transaction(
     params: ConfidentialTransferParams
-) -> Result<JsValue, JsError> {
+) -> Result<ConfidentialTransfer, WalletLibError> {
 
     let resource_substate_id = SubstateId::Resource(params.resource_address);
     let mut input_refs = vec![SubstateRequirement::new(resource_substate_id, None)];
 
     let amount =  Amount::new(params.amount);
     let confidential_amount = params.confidential_amount();
+    let revealed_amount = params.revealed_amount();
 
     let inputs_to_spend = resolved_inputs_for_transfer(
         SubstateId::Vault(params.source_vault_id),
@@ -319,11 +512,13 @@ pub fn build_confidential_transfer_transaction(
         params.resource_address,
         Amount::new(params.amount),
         params.input_selection,
+        params.coin_selection,
+        &params.output_key_indexes,
     )?;
 
     let resource_view_key = params.resource_substate.view_key().cloned();
 
-    let output_statement = create_confidential_proof_statement(
+    let (output_statement, _) = create_confidential_proof_statement(
         &params.source_private_key,
         &params.destination_public_key,
         confidential_amount,
@@ -332,32 +527,34 @@ pub fn build_confidential_transfer_transaction(
 
     let remaining_left_to_pay = amount
             .checked_sub_positive(inputs_to_spend.revealed)
-            .unwrap_or_else(|| {
-                panic!("Vault dow not contain a confidential resource")
-            });
+            .ok_or_else(|| {
+                WalletLibError::confidential_proof_failed("Revealed inputs exceed the transfer amount")
+            })?;
 
-    let change_confidential_amount = inputs_to_spend.total_confidential_amount() - remaining_left_to_pay;
-    let maybe_change_statement = if change_confidential_amount.is_zero() {
-        None
+    let change_confidential_amount = inputs_to_spend.total_confidential_amount()? - remaining_left_to_pay;
+    let (maybe_change_statement, change_output) = if change_confidential_amount.is_zero() {
+        (None, None)
     } else {
-        let statement = create_confidential_proof_statement(
+        let (statement, output) = create_change_output(
             &params.source_private_key,
-            &params.source_public_key,
+            SubstateId::Component(params.source_account_address),
+            SubstateId::Vault(params.source_vault_id),
             change_confidential_amount,
             resource_view_key,
         )?;
-    
-        Some(statement)
+
+        (Some(statement), Some(output))
     };
 
     let proof = create_withdraw_proof(
         &inputs_to_spend.confidential,
         inputs_to_spend.revealed,
         Some(&output_statement),
-        Amount::zero(),
+        revealed_amount,
         maybe_change_statement.as_ref(),
         Amount::zero()
-    )?;
+    )
+    .map_err(WalletLibError::confidential_proof_failed)?;
 
     let mut instructions = vec![];
 
@@ -405,11 +602,13 @@ pub fn build_confidential_transfer_transaction(
         args: args![Amount::new(params.fee)],
     });
 
-    let transaction = Transaction::builder()
+    let builder = Transaction::builder()
         .with_fee_instructions(instructions.to_vec())
-        .with_inputs(input_refs)
-        .sign(&params.source_private_key)
-        .build();
+        .with_inputs(input_refs);
+    let transaction = params.epoch_window.apply(builder).sign(&params.source_private_key).build();
 
-    Ok(serde_wasm_bindgen::to_value(&transaction)?)
+    Ok(ConfidentialTransfer {
+        transaction,
+        change_output,
+    })
 }
\ No newline at end of file
@@ -1,13 +1,197 @@
-use std::convert::Infallible;
+use std::collections::HashMap;
+use std::convert::{Infallible, TryInto};
+use std::fmt;
+use std::ops::RangeInclusive;
 
-use tari_engine_types::confidential::ValueLookupTable;
+use blake2::Blake2b;
+use digest::consts::U64;
+use tari_crypto::hash_domain;
+use tari_crypto::hashing::DomainSeparatedHasher;
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::{ByteArray, ByteArrayError};
+use tari_engine_types::confidential::{ElgamalVerifiableBalance, ValueLookupTable};
 
-pub struct AlwaysMissLookupTable;
+hash_domain!(TariSnapKeyDomain, "com.tari.snap.key_derivation", 1);
 
-impl ValueLookupTable for AlwaysMissLookupTable {
+const LABEL_OUTPUT_KEY: &str = "output_key";
+
+const LOOKUP_TABLE_MAGIC: &[u8; 4] = b"TSVT";
+const LOOKUP_TABLE_VERSION: u8 = 1;
+const LOOKUP_TABLE_HEADER_LEN: usize = LOOKUP_TABLE_MAGIC.len() + 1 + 8;
+const POINT_LEN: usize = 32;
+
+pub const DEFAULT_LOOKUP_TABLE_SIZE: u64 = 1 << 16;
+pub const MAX_LOOKUP_TABLE_SIZE: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupTableError {
+    InvalidSize(u64),
+    InvalidEncoding(String),
+}
+
+impl fmt::Display for LookupTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupTableError::InvalidSize(size) => write!(
+                f,
+                "Invalid lookup table size {}, it must be between 1 and {}",
+                size, MAX_LOOKUP_TABLE_SIZE
+            ),
+            LookupTableError::InvalidEncoding(reason) => write!(f, "Invalid lookup table encoding: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LookupTableError {}
+
+/// Baby-step giant-step table for decrypting Elgamal value points.
+/// It stores the baby steps `j·G` for every `j` below the table size, so a value `v` is found after at most
+/// `range / size` giant steps instead of trying every value in the range.
+pub struct BabyStepGiantStepTable {
+    baby_steps: Vec<[u8; POINT_LEN]>,
+    index: HashMap<[u8; POINT_LEN], u64>,
+}
+
+impl BabyStepGiantStepTable {
+    pub fn build(size: u64) -> Result<Self, LookupTableError> {
+        if size == 0 || size > MAX_LOOKUP_TABLE_SIZE {
+            return Err(LookupTableError::InvalidSize(size));
+        }
+
+        let generator = RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(1u64));
+        let mut baby_steps = Vec::with_capacity(size as usize);
+        let mut current = RistrettoPublicKey::default();
+        for _ in 0..size {
+            baby_steps.push(to_point_bytes(&current));
+            current = &current + &generator;
+        }
+
+        Ok(Self::from_baby_steps(baby_steps))
+    }
+
+    pub fn size(&self) -> u64 {
+        self.baby_steps.len() as u64
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(LOOKUP_TABLE_HEADER_LEN + self.baby_steps.len() * POINT_LEN);
+        bytes.extend_from_slice(LOOKUP_TABLE_MAGIC);
+        bytes.push(LOOKUP_TABLE_VERSION);
+        bytes.extend_from_slice(&self.size().to_le_bytes());
+        for point in &self.baby_steps {
+            bytes.extend_from_slice(point);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LookupTableError> {
+        if bytes.len() < LOOKUP_TABLE_HEADER_LEN || &bytes[..LOOKUP_TABLE_MAGIC.len()] != LOOKUP_TABLE_MAGIC {
+            return Err(LookupTableError::InvalidEncoding("missing header".to_string()));
+        }
+        let version = bytes[LOOKUP_TABLE_MAGIC.len()];
+        if version != LOOKUP_TABLE_VERSION {
+            return Err(LookupTableError::InvalidEncoding(format!("unsupported version {}", version)));
+        }
+        let size_bytes: [u8; 8] = bytes[LOOKUP_TABLE_MAGIC.len() + 1..LOOKUP_TABLE_HEADER_LEN]
+            .try_into()
+            .expect("slice has the length of a u64");
+        let size = u64::from_le_bytes(size_bytes);
+        if size == 0 || size > MAX_LOOKUP_TABLE_SIZE {
+            return Err(LookupTableError::InvalidSize(size));
+        }
+
+        let points = &bytes[LOOKUP_TABLE_HEADER_LEN..];
+        if points.len() as u64 != size * POINT_LEN as u64 {
+            return Err(LookupTableError::InvalidEncoding(format!(
+                "expected {} points but found {} bytes",
+                size,
+                points.len()
+            )));
+        }
+        let baby_steps = points
+            .chunks_exact(POINT_LEN)
+            .map(|chunk| chunk.try_into().expect("chunk has the length of a point"))
+            .collect();
+
+        Ok(Self::from_baby_steps(baby_steps))
+    }
+
+    /// Finds the value `v` in `value_range` such that `value_point = v·G`.
+    /// Every match is checked against the value point, so a corrupted table can cause a miss but never a wrong value.
+    pub fn find_value(&self, value_point: &RistrettoPublicKey, value_range: RangeInclusive<u64>) -> Option<u64> {
+        let (start, end) = value_range.into_inner();
+        if start > end {
+            return None;
+        }
+
+        let size = self.size();
+        let giant_step = RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(size));
+        // a table of size 1 over the whole u64 range needs one more step than fits in a u64, which no search reaches
+        let giant_steps = ((end - start) / size).saturating_add(1);
+        // Search from the start of the range: Q_i = V - start·G - i·size·G
+        let mut current = value_point - &RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(start));
+
+        for i in 0..giant_steps {
+            if let Some(baby_step) = self.index.get(&to_point_bytes(&current)) {
+                let value = i
+                    .checked_mul(size)
+                    .and_then(|v| v.checked_add(start))
+                    .and_then(|v| v.checked_add(*baby_step))
+                    .filter(|v| *v <= end);
+                if let Some(value) = value {
+                    if RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(value)) == *value_point {
+                        return Some(value);
+                    }
+                }
+            }
+            current = &current - &giant_step;
+        }
+
+        None
+    }
+
+    /// Decrypts the value of a viewable balance with the secret view key of the resource
+    pub fn decrypt_balance(
+        &self,
+        secret_view_key: &RistrettoSecretKey,
+        balance: &ElgamalVerifiableBalance,
+        value_range: RangeInclusive<u64>,
+    ) -> Option<u64> {
+        // V = E - k·R
+        let value_point = balance.encrypted.as_public_key() - secret_view_key * &balance.public_nonce;
+        self.find_value(&value_point, value_range)
+    }
+
+    fn from_baby_steps(baby_steps: Vec<[u8; POINT_LEN]>) -> Self {
+        let index = baby_steps
+            .iter()
+            .enumerate()
+            .map(|(j, point)| (*point, j as u64))
+            .collect();
+        Self { baby_steps, index }
+    }
+}
+
+impl ValueLookupTable for BabyStepGiantStepTable {
     type Error = Infallible;
 
-    fn lookup(&mut self, _value: u64) -> Result<Option<[u8; 32]>, Self::Error> {
-        Ok(None)
+    fn lookup(&mut self, value: u64) -> Result<Option<[u8; 32]>, Self::Error> {
+        Ok(self.baby_steps.get(value as usize).copied())
     }
-}
\ No newline at end of file
+}
+
+fn to_point_bytes(point: &RistrettoPublicKey) -> [u8; POINT_LEN] {
+    point.as_bytes().try_into().expect("Ristretto points are 32 bytes")
+}
+
+/// Derives the secret key at `index` from the account secret key.
+/// The account key and the index are length-prefixed by the domain separated hasher, so different indexes never
+/// collide and the derived keys reveal nothing about the account key.
+pub fn derive_output_key(account_key: &RistrettoSecretKey, index: u64) -> Result<RistrettoSecretKey, ByteArrayError> {
+    let hash = DomainSeparatedHasher::<Blake2b<U64>, TariSnapKeyDomain>::new_with_label(LABEL_OUTPUT_KEY)
+        .chain(account_key.as_bytes())
+        .chain(index.to_le_bytes())
+        .finalize();
+    RistrettoSecretKey::from_uniform_bytes(hash.as_ref())
+}
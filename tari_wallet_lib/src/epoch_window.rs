@@ -0,0 +1,63 @@
+//! Epoch bounds of a transaction. The network rejects transactions outside of their window, so a signed transaction
+//! cannot be replayed long after it was built.
+
+use serde::{Deserialize, Serialize};
+use tari_dan_common_types::Epoch;
+use tari_transaction::{Transaction, TransactionBuilder};
+
+use crate::error::WalletLibError;
+
+/// Number of epochs a transaction is valid for when the window is built from the current epoch
+pub const DEFAULT_VALIDITY_EPOCHS: u64 = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochWindow {
+    #[serde(default)]
+    pub min_epoch: Option<u64>,
+    #[serde(default)]
+    pub max_epoch: Option<u64>,
+}
+
+impl EpochWindow {
+    pub fn new(min_epoch: Option<u64>, max_epoch: Option<u64>) -> Result<Self, WalletLibError> {
+        Self { min_epoch, max_epoch }.validated()
+    }
+
+    /// Window starting at `current_epoch` and lasting `validity_epochs` epochs
+    pub fn from_current_epoch(current_epoch: u64, validity_epochs: u64) -> Self {
+        Self {
+            min_epoch: Some(current_epoch),
+            max_epoch: Some(current_epoch.saturating_add(validity_epochs)),
+        }
+    }
+
+    pub fn of_transaction(transaction: &Transaction) -> Self {
+        Self {
+            min_epoch: transaction.min_epoch().map(|e| e.as_u64()),
+            max_epoch: transaction.max_epoch().map(|e| e.as_u64()),
+        }
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.min_epoch.is_none() && self.max_epoch.is_none()
+    }
+
+    /// Checks the bounds, which is needed for windows deserialized from JavaScript
+    pub fn validated(self) -> Result<Self, WalletLibError> {
+        if let (Some(min_epoch), Some(max_epoch)) = (self.min_epoch, self.max_epoch) {
+            if min_epoch > max_epoch {
+                return Err(WalletLibError::invalid_argument(
+                    "epoch_window",
+                    format!("the min epoch {} is after the max epoch {}", min_epoch, max_epoch),
+                ));
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn apply(&self, builder: TransactionBuilder) -> TransactionBuilder {
+        builder
+            .with_min_epoch(self.min_epoch.map(Epoch))
+            .with_max_epoch(self.max_epoch.map(Epoch))
+    }
+}
@@ -0,0 +1,231 @@
+//! Error type shared by every exported function.
+//! Each variant has a stable code, so the snap can react to specific failures without matching on messages.
+//! In JavaScript the errors are `Error` objects with extra `code` and `details` properties.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::cipher_seed::CipherSeedError;
+use crate::coin_selection::InsufficientFundsError;
+use crate::crypto::LookupTableError;
+use crate::keystore::KeystoreError;
+use crate::message_signing::MessageSignatureError;
+use crate::sign_in::SignInError;
+
+/// The serialized `code` and `details` of each variant are part of the public API, do not rename them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "code", content = "details", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WalletLibError {
+    InvalidArgument { argument: String, reason: String },
+    InvalidPublicKey { reason: String },
+    InvalidPrivateKey { reason: String },
+    InvalidMnemonic { reason: String },
+    KeyDerivationFailed { reason: String },
+    InsufficientFunds { requested: u64, available: u64, shortfall: u64 },
+    NotAConfidentialVault,
+    ConfidentialProofFailed { reason: String },
+    InvalidSignature { reason: String },
+    SigningFailed { reason: String },
+    SignInRejected { reason: String },
+    WrongPassword,
+    InvalidKeystore { reason: String },
+    InvalidLookupTable { reason: String },
+    InvalidTransaction { reason: String },
+    EncodingFailed { reason: String },
+}
+
+impl WalletLibError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            WalletLibError::InvalidArgument { .. } => "INVALID_ARGUMENT",
+            WalletLibError::InvalidPublicKey { .. } => "INVALID_PUBLIC_KEY",
+            WalletLibError::InvalidPrivateKey { .. } => "INVALID_PRIVATE_KEY",
+            WalletLibError::InvalidMnemonic { .. } => "INVALID_MNEMONIC",
+            WalletLibError::KeyDerivationFailed { .. } => "KEY_DERIVATION_FAILED",
+            WalletLibError::InsufficientFunds { .. } => "INSUFFICIENT_FUNDS",
+            WalletLibError::NotAConfidentialVault => "NOT_A_CONFIDENTIAL_VAULT",
+            WalletLibError::ConfidentialProofFailed { .. } => "CONFIDENTIAL_PROOF_FAILED",
+            WalletLibError::InvalidSignature { .. } => "INVALID_SIGNATURE",
+            WalletLibError::SigningFailed { .. } => "SIGNING_FAILED",
+            WalletLibError::SignInRejected { .. } => "SIGN_IN_REJECTED",
+            WalletLibError::WrongPassword => "WRONG_PASSWORD",
+            WalletLibError::InvalidKeystore { .. } => "INVALID_KEYSTORE",
+            WalletLibError::InvalidLookupTable { .. } => "INVALID_LOOKUP_TABLE",
+            WalletLibError::InvalidTransaction { .. } => "INVALID_TRANSACTION",
+            WalletLibError::EncodingFailed { .. } => "ENCODING_FAILED",
+        }
+    }
+
+    pub fn invalid_argument<E: fmt::Display>(argument: &str, reason: E) -> Self {
+        WalletLibError::InvalidArgument {
+            argument: argument.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn invalid_public_key<E: fmt::Debug>(e: E) -> Self {
+        WalletLibError::InvalidPublicKey {
+            reason: format!("{:?}", e),
+        }
+    }
+
+    pub fn invalid_private_key<E: fmt::Debug>(e: E) -> Self {
+        WalletLibError::InvalidPrivateKey {
+            reason: format!("{:?}", e),
+        }
+    }
+
+    pub fn key_derivation_failed<E: fmt::Debug>(e: E) -> Self {
+        WalletLibError::KeyDerivationFailed {
+            reason: format!("{:?}", e),
+        }
+    }
+
+    pub fn insufficient_funds(requested: u64, available: u64) -> Self {
+        WalletLibError::InsufficientFunds {
+            requested,
+            available,
+            shortfall: requested.saturating_sub(available),
+        }
+    }
+
+    pub fn confidential_proof_failed<E: fmt::Display>(e: E) -> Self {
+        WalletLibError::ConfidentialProofFailed { reason: e.to_string() }
+    }
+
+    pub fn invalid_transaction<T: Into<String>>(reason: T) -> Self {
+        WalletLibError::InvalidTransaction { reason: reason.into() }
+    }
+
+    pub fn encoding_failed<E: fmt::Display>(e: E) -> Self {
+        WalletLibError::EncodingFailed { reason: e.to_string() }
+    }
+}
+
+impl fmt::Display for WalletLibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletLibError::InvalidArgument { argument, reason } => write!(f, "Invalid {}: {}", argument, reason),
+            WalletLibError::InvalidPublicKey { reason } => write!(f, "Could not parse public key: {}", reason),
+            WalletLibError::InvalidPrivateKey { reason } => write!(f, "Could not parse private key: {}", reason),
+            WalletLibError::InvalidMnemonic { reason } => write!(f, "Invalid mnemonic: {}", reason),
+            WalletLibError::KeyDerivationFailed { reason } => write!(f, "Could not derive key: {}", reason),
+            WalletLibError::InsufficientFunds {
+                requested,
+                available,
+                shortfall,
+            } => write!(
+                f,
+                "Insufficient funds: requested {} but only {} is available (short by {})",
+                requested, available, shortfall
+            ),
+            WalletLibError::NotAConfidentialVault => write!(f, "The vault does not contain a confidential resource"),
+            WalletLibError::ConfidentialProofFailed { reason } => {
+                write!(f, "Could not create the confidential proof: {}", reason)
+            },
+            WalletLibError::InvalidSignature { reason } => write!(f, "Invalid signature: {}", reason),
+            WalletLibError::SigningFailed { reason } => write!(f, "Could not sign: {}", reason),
+            WalletLibError::SignInRejected { reason } => write!(f, "Sign-in rejected: {}", reason),
+            WalletLibError::WrongPassword => {
+                write!(f, "Could not decrypt the keystore: wrong password or the keystore was modified")
+            },
+            WalletLibError::InvalidKeystore { reason } => write!(f, "Invalid keystore: {}", reason),
+            WalletLibError::InvalidLookupTable { reason } => write!(f, "Invalid lookup table: {}", reason),
+            WalletLibError::InvalidTransaction { reason } => write!(f, "Invalid transaction: {}", reason),
+            WalletLibError::EncodingFailed { reason } => write!(f, "Could not encode the result: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for WalletLibError {}
+
+impl From<WalletLibError> for JsValue {
+    fn from(e: WalletLibError) -> Self {
+        let error = js_sys::Error::new(&e.to_string());
+        let details = e
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .ok()
+            .and_then(|value| js_sys::Reflect::get(&value, &"details".into()).ok())
+            .unwrap_or(JsValue::UNDEFINED);
+        // Setting properties on a fresh Error object cannot fail
+        let _ = js_sys::Reflect::set(&error, &"code".into(), &e.code().into());
+        let _ = js_sys::Reflect::set(&error, &"details".into(), &details);
+        error.into()
+    }
+}
+
+impl From<CipherSeedError> for WalletLibError {
+    fn from(e: CipherSeedError) -> Self {
+        match e {
+            CipherSeedError::KeyDerivation(reason) => WalletLibError::KeyDerivationFailed { reason },
+            e => WalletLibError::InvalidMnemonic { reason: e.to_string() },
+        }
+    }
+}
+
+impl From<InsufficientFundsError> for WalletLibError {
+    fn from(e: InsufficientFundsError) -> Self {
+        WalletLibError::InsufficientFunds {
+            requested: e.requested,
+            available: e.available,
+            shortfall: e.shortfall,
+        }
+    }
+}
+
+impl From<LookupTableError> for WalletLibError {
+    fn from(e: LookupTableError) -> Self {
+        WalletLibError::InvalidLookupTable { reason: e.to_string() }
+    }
+}
+
+impl From<KeystoreError> for WalletLibError {
+    fn from(e: KeystoreError) -> Self {
+        match e {
+            KeystoreError::EmptyPassword => {
+                WalletLibError::invalid_argument("password", "the password cannot be empty")
+            },
+            KeystoreError::DecryptionFailed => WalletLibError::WrongPassword,
+            KeystoreError::KeyDerivation(reason) => WalletLibError::KeyDerivationFailed { reason },
+            e => WalletLibError::InvalidKeystore { reason: e.to_string() },
+        }
+    }
+}
+
+impl From<MessageSignatureError> for WalletLibError {
+    fn from(e: MessageSignatureError) -> Self {
+        match e {
+            MessageSignatureError::MissingDomain => {
+                WalletLibError::invalid_argument("domain", "a domain is required to sign or verify messages")
+            },
+            MessageSignatureError::InvalidDomain(reason) => WalletLibError::invalid_argument("domain", reason),
+            MessageSignatureError::InvalidSignature(reason) => WalletLibError::InvalidSignature { reason },
+            MessageSignatureError::SigningFailed(reason) => WalletLibError::SigningFailed { reason },
+        }
+    }
+}
+
+impl From<SignInError> for WalletLibError {
+    fn from(e: SignInError) -> Self {
+        match e {
+            SignInError::InvalidMessage(reason) => WalletLibError::InvalidArgument {
+                argument: "message".to_string(),
+                reason,
+            },
+            SignInError::Signature(e) => e.into(),
+            e => WalletLibError::SignInRejected { reason: e.to_string() },
+        }
+    }
+}
+
+/// Decodes an argument received from JavaScript, naming it in the error
+pub(crate) fn from_js_value<T: DeserializeOwned>(argument: &str, value: JsValue) -> Result<T, WalletLibError> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| WalletLibError::invalid_argument(argument, e))
+}
+
+pub(crate) fn to_js_value<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, WalletLibError> {
+    serde_wasm_bindgen::to_value(value).map_err(WalletLibError::encoding_failed)
+}
@@ -0,0 +1,130 @@
+//! Fee estimation from the result of a dry run.
+//! A transaction is first submitted as a dry run with a generous fee, then rebuilt with the fee it actually consumed
+//! plus a safety margin, as the cost can change slightly between the dry run and the real execution.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_engine_types::commit_result::{FinalizeResult, TransactionResult};
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::prelude::Amount;
+use tari_transaction::Transaction;
+
+use crate::epoch_window::EpochWindow;
+use crate::error::WalletLibError;
+
+const PAY_FEE_METHOD: &str = "pay_fee";
+
+/// Safety margin added to the fee consumed by a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeMargin {
+    /// Percentage of the consumed fee, rounded up
+    pub percent: u32,
+    /// Lower bound of the margin, for transactions that consume very small fees
+    pub minimum: i64,
+}
+
+impl Default for FeeMargin {
+    fn default() -> Self {
+        Self {
+            percent: 20,
+            minimum: 100,
+        }
+    }
+}
+
+impl FeeMargin {
+    pub fn margin_for(&self, fee: i64) -> i64 {
+        let percentage = (i128::from(fee) * i128::from(self.percent) + 99) / 100;
+        let percentage = i64::try_from(percentage).unwrap_or(i64::MAX);
+        percentage.max(self.minimum)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Exact fee consumed by the dry run
+    pub fee_consumed: i64,
+    pub margin: i64,
+    /// Fee to pay in the real transaction
+    pub fee: i64,
+}
+
+impl FeeEstimate {
+    pub fn new(fee_consumed: i64, margin: &FeeMargin) -> Self {
+        let margin = margin.margin_for(fee_consumed);
+        Self {
+            fee_consumed,
+            margin,
+            fee: fee_consumed.saturating_add(margin),
+        }
+    }
+}
+
+/// Estimates the fee of a transaction from the finalize result of its dry run.
+/// Rejected dry runs are an error, as the fee they consumed says nothing about a successful execution.
+pub fn estimate_fee(finalize_result: &FinalizeResult, margin: &FeeMargin) -> Result<FeeEstimate, WalletLibError> {
+    match &finalize_result.result {
+        TransactionResult::Accept(_) => {},
+        TransactionResult::AcceptFeeRejectRest(_, reason) | TransactionResult::Reject(reason) => {
+            return Err(WalletLibError::invalid_transaction(format!(
+                "the dry run was rejected: {}",
+                reason
+            )))
+        },
+    }
+    let fee_consumed = finalize_result.fee_receipt.total_fees_charged().value();
+    Ok(FeeEstimate::new(fee_consumed, margin))
+}
+
+/// Rebuilds the transaction paying `fee` and signs it again with `secret_key`, keeping its epoch window.
+/// The transaction must pay its fees with a single `pay_fee` call, and must have been signed only by `secret_key`,
+/// as changing the fee invalidates every existing signature.
+pub fn rebuild_with_fee(
+    transaction: &Transaction,
+    fee: i64,
+    secret_key: &RistrettoSecretKey,
+) -> Result<Transaction, WalletLibError> {
+    if fee <= 0 {
+        return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+    }
+    if transaction.signatures().len() > 1 {
+        return Err(WalletLibError::invalid_transaction(
+            "transactions with several signers cannot be re-signed with a single key",
+        ));
+    }
+    let public_key = RistrettoPublicKey::from_secret_key(secret_key);
+    if transaction.signatures().iter().any(|s| *s.public_key() != public_key) {
+        return Err(WalletLibError::invalid_transaction(
+            "the transaction is signed by another key, it cannot be re-signed with this one",
+        ));
+    }
+
+    let mut fee_instructions = transaction.fee_instructions().to_vec();
+    let mut pay_fee_calls = fee_instructions.iter_mut().filter_map(|instruction| match instruction {
+        Instruction::CallMethod { method, args, .. } if *method == PAY_FEE_METHOD => Some(args),
+        _ => None,
+    });
+    match (pay_fee_calls.next(), pay_fee_calls.next()) {
+        (Some(args), None) => *args = args![Amount::new(fee)],
+        (None, _) => return Err(WalletLibError::invalid_transaction("the transaction does not pay any fees")),
+        (Some(_), Some(_)) => {
+            return Err(WalletLibError::invalid_transaction(
+                "the transaction pays fees from several calls, it is not clear which one to change",
+            ))
+        },
+    }
+
+    let builder = Transaction::builder()
+        .with_fee_instructions(fee_instructions)
+        .with_instructions(transaction.instructions().to_vec())
+        .with_inputs(transaction.inputs().iter().cloned().collect::<Vec<_>>());
+    Ok(EpochWindow::of_transaction(transaction)
+        .apply(builder)
+        .sign(secret_key)
+        .build())
+}
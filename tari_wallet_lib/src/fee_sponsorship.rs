@@ -0,0 +1,185 @@
+//! Transactions whose fees are paid by a sponsor account, e.g. to onboard users that do not hold any XTR yet.
+//! The user builds and signs the transaction first, then hands the partially signed transaction JSON to the sponsor,
+//! who checks it before adding their own signature.
+//!
+//! The signature of the sponsor does more than pay the fee: every instruction runs with the badge of each signer, so
+//! any component that accepts the badge of the sponsor could be called with their authority. For this reason the
+//! sponsor does not only check the fee instructions, it also restricts the main instructions to the accounts of the
+//! other signers, account creation, workspace moves and the components and templates of a `SponsorPolicy` allowlist.
+//! The allowlist must only contain components and templates that cannot act on behalf of their caller, as the
+//! components they call in turn are not checked.
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{Amount, ComponentAddress};
+use tari_transaction::{SubstateRequirement, Transaction};
+
+use crate::component::account_address_from_public_key;
+use crate::epoch_window::EpochWindow;
+use crate::error::WalletLibError;
+
+const PAY_FEE_METHOD: &str = "pay_fee";
+
+/// Builds a transaction that runs `instructions` signed by the user, paying `fee` from the sponsor account.
+/// The result is only signed by the user, it must be signed by the sponsor with `sponsor_transaction`.
+pub fn build_sponsored_transaction(
+    user_secret_key: &RistrettoSecretKey,
+    sponsor_public_key: &RistrettoPublicKey,
+    fee: i64,
+    instructions: Vec<Instruction>,
+    inputs: Vec<SubstateRequirement>,
+    epoch_window: EpochWindow,
+) -> Result<Transaction, WalletLibError> {
+    if fee <= 0 {
+        return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+    }
+    if instructions.is_empty() {
+        return Err(WalletLibError::invalid_transaction("the transaction does not have any instructions"));
+    }
+    let sponsor_account_address = account_address_from_public_key(sponsor_public_key);
+    if instructions.iter().any(|i| calls_component(i, &sponsor_account_address)) {
+        return Err(WalletLibError::invalid_transaction(
+            "the instructions cannot call the sponsor account",
+        ));
+    }
+
+    let builder = Transaction::builder()
+        .with_fee_instructions(vec![Instruction::CallMethod {
+            component_address: sponsor_account_address,
+            method: PAY_FEE_METHOD.to_string(),
+            args: args![Amount::new(fee)],
+        }])
+        .with_instructions(instructions)
+        .with_inputs(inputs);
+    Ok(epoch_window.apply(builder).sign(user_secret_key).build())
+}
+
+/// What the sponsor agrees to sign besides paying the fee
+#[derive(Debug, Clone, Default)]
+pub struct SponsorPolicy {
+    /// Maximum fee paid by the sponsor, any fee if not set
+    pub max_fee: Option<i64>,
+    /// Components the instructions can call besides the accounts of the other signers
+    pub allowed_components: Vec<ComponentAddress>,
+    /// Templates whose functions the instructions can call
+    pub allowed_templates: Vec<TemplateAddress>,
+}
+
+/// Adds the signature of the sponsor to a transaction built with `build_sponsored_transaction`.
+/// The sponsor only signs if every existing signature is valid, the fee instructions do nothing but pay fees from
+/// their account within the policy, and the main instructions are allowed by the policy (see the module docs).
+pub fn sponsor_transaction(
+    transaction: Transaction,
+    sponsor_secret_key: &RistrettoSecretKey,
+    policy: &SponsorPolicy,
+) -> Result<Transaction, WalletLibError> {
+    let sponsor_public_key = RistrettoPublicKey::from_secret_key(sponsor_secret_key);
+    let sponsor_account_address = account_address_from_public_key(&sponsor_public_key);
+
+    let signers = transaction_signers(&transaction);
+    if signers.is_empty() {
+        return Err(WalletLibError::invalid_transaction(
+            "the transaction must be signed by the user before the sponsor",
+        ));
+    }
+    if signers.contains(&sponsor_public_key) {
+        return Err(WalletLibError::invalid_transaction(
+            "the transaction is already signed by the sponsor",
+        ));
+    }
+    if !transaction.verify_all_signatures() {
+        return Err(WalletLibError::invalid_transaction(
+            "the transaction has an invalid signature",
+        ));
+    }
+
+    let signer_accounts: Vec<ComponentAddress> = signers.iter().map(account_address_from_public_key).collect();
+    for (i, instruction) in transaction.instructions().iter().enumerate() {
+        if calls_component(instruction, &sponsor_account_address) {
+            return Err(WalletLibError::invalid_transaction(
+                "the instructions cannot call the sponsor account",
+            ));
+        }
+        if !is_allowed(instruction, &signer_accounts, policy) {
+            return Err(WalletLibError::invalid_transaction(format!(
+                "instruction {} is not allowed by the sponsor",
+                i
+            )));
+        }
+    }
+
+    let fee = sponsored_fee(transaction.fee_instructions(), &sponsor_account_address)?;
+    if let Some(max_fee) = policy.max_fee {
+        if fee > max_fee {
+            return Err(WalletLibError::invalid_transaction(format!(
+                "the transaction pays a fee of {}, which is over the maximum of {}",
+                fee, max_fee
+            )));
+        }
+    }
+
+    Ok(transaction.sign(sponsor_secret_key))
+}
+
+/// Public keys of the parties that have signed the transaction so far
+pub fn transaction_signers(transaction: &Transaction) -> Vec<RistrettoPublicKey> {
+    transaction
+        .signatures()
+        .iter()
+        .map(|signature| signature.public_key().clone())
+        .collect()
+}
+
+// Total fee paid by the fee instructions, which must all be `pay_fee` calls on the sponsor account
+fn sponsored_fee(
+    fee_instructions: &[Instruction],
+    sponsor_account_address: &ComponentAddress,
+) -> Result<i64, WalletLibError> {
+    if fee_instructions.is_empty() {
+        return Err(WalletLibError::invalid_transaction("the transaction does not pay any fees"));
+    }
+    fee_instructions.iter().try_fold(0i64, |total, instruction| {
+        let amount = pay_fee_amount(instruction, sponsor_account_address).ok_or_else(|| {
+            WalletLibError::invalid_transaction(
+                "the fee instructions can only pay positive fees from the sponsor account",
+            )
+        })?;
+        total
+            .checked_add(amount.value())
+            .ok_or_else(|| WalletLibError::invalid_transaction("the total fee overflows"))
+    })
+}
+
+// A negative fee would offset the others in the total checked against the policy, so only positive fees are accepted
+fn pay_fee_amount(instruction: &Instruction, account_address: &ComponentAddress) -> Option<Amount> {
+    match instruction {
+        Instruction::CallMethod {
+            component_address,
+            method,
+            args,
+        } if component_address == account_address && method == PAY_FEE_METHOD => match args.as_slice() {
+            [Arg::Literal(bytes)] => tari_bor::decode::<Amount>(bytes).ok().filter(|amount| amount.value() > 0),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_allowed(instruction: &Instruction, signer_accounts: &[ComponentAddress], policy: &SponsorPolicy) -> bool {
+    match instruction {
+        Instruction::CallMethod { component_address, .. } => {
+            signer_accounts.contains(component_address) || policy.allowed_components.contains(component_address)
+        },
+        Instruction::CallFunction { template_address, .. } => policy.allowed_templates.contains(template_address),
+        Instruction::CreateAccount { .. } | Instruction::PutLastInstructionOutputOnWorkspace { .. } => true,
+        _ => false,
+    }
+}
+
+fn calls_component(instruction: &Instruction, address: &ComponentAddress) -> bool {
+    matches!(instruction, Instruction::CallMethod { component_address, .. } if component_address == address)
+}
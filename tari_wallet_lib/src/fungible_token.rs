@@ -0,0 +1,154 @@
+//! Creation of new fungible resources, with the initial supply deposited into the account of the creator.
+//! The engine has no instruction to create a resource, only templates can do it, so the transaction calls
+//! `CREATE_TOKEN_FUNCTION` of the token template in `templates/fungible_token`, which has this signature:
+//! `fn create_fungible_token(symbol: String, divisibility: u8, initial_supply: Amount, metadata: Metadata,
+//! owner_rule: OwnerRule, confidential: bool, view_key: Option<RistrettoPublicKeyBytes>) -> Bucket`
+//! The template must be published to the network, its tests execute the instructions built here.
+
+use serde::{Deserialize, Serialize};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_crypto::tari_utilities::ByteArray;
+use tari_engine_types::commit_result::{FinalizeResult, TransactionResult};
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::{SubstateId, SubstateValue};
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::auth::OwnerRule;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{Amount, ComponentAddress, ResourceAddress, ResourceType, RistrettoPublicKeyBytes};
+
+use crate::error::WalletLibError;
+use crate::metadata::{encode_metadata_fields, MetadataField};
+
+pub const CREATE_TOKEN_FUNCTION: &str = "create_fungible_token";
+const INITIAL_SUPPLY_BUCKET: &str = "initial_supply";
+
+/// Parameters of a new fungible resource, as passed from JS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FungibleTokenParams {
+    pub symbol: String,
+    /// Number of decimal places used to display amounts
+    pub divisibility: u8,
+    pub initial_supply: i64,
+    /// Same fields as in `encode_metadata`
+    #[serde(default)]
+    pub metadata: Vec<MetadataField>,
+    #[serde(default = "default_owner_rule")]
+    pub owner_rule: OwnerRule,
+    #[serde(default)]
+    pub confidential: bool,
+    /// Hex public key that can view the confidential balances of the resource
+    #[serde(default)]
+    pub view_key: Option<String>,
+}
+
+fn default_owner_rule() -> OwnerRule {
+    OwnerRule::OwnedBySigner
+}
+
+#[derive(Debug, Clone)]
+pub struct FungibleToken {
+    symbol: String,
+    divisibility: u8,
+    initial_supply: Amount,
+    metadata: Vec<MetadataField>,
+    owner_rule: OwnerRule,
+    confidential: bool,
+    view_key: Option<RistrettoPublicKeyBytes>,
+}
+
+impl FungibleToken {
+    pub fn new(params: FungibleTokenParams) -> Result<Self, WalletLibError> {
+        if params.symbol.trim().is_empty() {
+            return Err(WalletLibError::invalid_argument("symbol", "the symbol cannot be empty"));
+        }
+        if params.initial_supply < 0 {
+            return Err(WalletLibError::invalid_argument(
+                "initial_supply",
+                "the initial supply cannot be negative",
+            ));
+        }
+        let view_key = match params.view_key {
+            Some(_) if !params.confidential => {
+                return Err(WalletLibError::invalid_argument(
+                    "view_key",
+                    "only confidential resources have a view key",
+                ))
+            },
+            Some(view_key) => {
+                let view_key = RistrettoPublicKey::from_hex(&view_key).map_err(WalletLibError::invalid_public_key)?;
+                let view_key = RistrettoPublicKeyBytes::from_bytes(view_key.as_bytes())
+                    .map_err(WalletLibError::invalid_public_key)?;
+                Some(view_key)
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            symbol: params.symbol,
+            divisibility: params.divisibility,
+            initial_supply: Amount::new(params.initial_supply),
+            metadata: params.metadata,
+            owner_rule: params.owner_rule,
+            confidential: params.confidential,
+            view_key,
+        })
+    }
+
+    /// Creates the resource with the token template and deposits the initial supply into `account_address`
+    pub fn instructions(
+        &self,
+        template_address: TemplateAddress,
+        account_address: ComponentAddress,
+    ) -> Result<Vec<Instruction>, WalletLibError> {
+        let mut args = args![self.symbol.clone(), self.divisibility, self.initial_supply];
+        // the metadata is encoded exactly as by `encode_metadata`
+        args.push(Arg::Literal(encode_metadata_fields(self.metadata.clone())?));
+        args.extend(args![self.owner_rule.clone(), self.confidential, self.view_key.clone()]);
+
+        Ok(vec![
+            Instruction::CallFunction {
+                template_address,
+                function: CREATE_TOKEN_FUNCTION.to_string(),
+                args,
+            },
+            Instruction::PutLastInstructionOutputOnWorkspace {
+                key: INITIAL_SUPPLY_BUCKET.as_bytes().to_vec(),
+            },
+            Instruction::CallMethod {
+                component_address: account_address,
+                method: "deposit".to_string(),
+                args: args![Workspace(INITIAL_SUPPLY_BUCKET)],
+            },
+        ])
+    }
+}
+
+/// Address of the fungible resource created by a transaction, from the finalize result of its dry run.
+/// Resource addresses are only assigned on execution, so the dry run result is the way to know it before submitting.
+pub fn created_resource_address(finalize_result: &FinalizeResult) -> Result<ResourceAddress, WalletLibError> {
+    let diff = match &finalize_result.result {
+        TransactionResult::Accept(diff) => diff,
+        TransactionResult::AcceptFeeRejectRest(_, reason) | TransactionResult::Reject(reason) => {
+            return Err(WalletLibError::invalid_transaction(format!(
+                "the dry run was rejected: {}",
+                reason
+            )))
+        },
+    };
+
+    // resources that already existed are also up substates when their total supply changes
+    let existing: Vec<&SubstateId> = diff.down_iter().map(|(id, _)| id).collect();
+    diff.up_iter()
+        .filter(|(id, _)| !existing.contains(&id))
+        .find_map(|(id, substate)| match (id, substate.substate_value()) {
+            (SubstateId::Resource(address), SubstateValue::Resource(resource))
+                if matches!(resource.resource_type(), ResourceType::Fungible | ResourceType::Confidential) =>
+            {
+                Some(*address)
+            },
+            _ => None,
+        })
+        .ok_or_else(|| WalletLibError::invalid_transaction("the transaction does not create a fungible resource"))
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use serde::{Deserialize, Serialize};
+use tari_crypto::hashing::DomainSeparatedHasher;
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::{from_hex, Hex};
+use wasm_bindgen::prelude::*;
+
+use crate::component::get_account_address_from_public_key;
+use crate::crypto::TariSnapKeyDomain;
+use crate::error::WalletLibError;
+
+const LABEL_METAMASK_ENTROPY: &str = "metamask_entropy";
+const METAMASK_KEY_LEN: usize = 32;
+
+/// Scheme used to turn the MetaMask BIP-44 private key into a Ristretto secret key
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyDerivationVersion {
+    /// Uniform bytes read from the ASCII characters of the hex encoded key.
+    /// Kept so the accounts created by the first releases of the snap remain accessible.
+    V0 = 0,
+    /// Domain separated hash of the raw key bytes
+    V1 = 1,
+}
+
+impl KeyDerivationVersion {
+    pub const ALL: [KeyDerivationVersion; 2] = [KeyDerivationVersion::V0, KeyDerivationVersion::V1];
+    pub const LATEST: KeyDerivationVersion = KeyDerivationVersion::V1;
+}
+
+impl Default for KeyDerivationVersion {
+    // Existing callers that do not specify a version keep deriving the same keys
+    fn default() -> Self {
+        KeyDerivationVersion::V0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyDerivationAccount {
+    pub version: KeyDerivationVersion,
+    pub public_key: String,
+    pub account_address: String,
+    pub balance: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyDerivationReport {
+    pub accounts: Vec<KeyDerivationAccount>,
+    pub funded_versions: Vec<KeyDerivationVersion>,
+    pub recommended_version: KeyDerivationVersion,
+}
+
+pub fn derive_ristretto_private_key(
+    ecdsa_str: &str,
+    version: KeyDerivationVersion,
+) -> Result<RistrettoSecretKey, WalletLibError> {
+    let no_prefix_hex = ecdsa_str.strip_prefix("0x").unwrap_or(ecdsa_str);
+    match version {
+        KeyDerivationVersion::V0 => RistrettoSecretKey::from_uniform_bytes(no_prefix_hex.as_bytes())
+            .map_err(WalletLibError::invalid_private_key),
+        KeyDerivationVersion::V1 => {
+            let key_bytes = from_hex(no_prefix_hex).map_err(WalletLibError::invalid_private_key)?;
+            if key_bytes.len() != METAMASK_KEY_LEN {
+                return Err(WalletLibError::InvalidPrivateKey {
+                    reason: format!("expected {} bytes but got {}", METAMASK_KEY_LEN, key_bytes.len()),
+                });
+            }
+            let hash = DomainSeparatedHasher::<Blake2b<U64>, TariSnapKeyDomain>::new_with_label(LABEL_METAMASK_ENTROPY)
+                .chain(&key_bytes)
+                .finalize();
+            RistrettoSecretKey::from_uniform_bytes(hash.as_ref()).map_err(WalletLibError::key_derivation_failed)
+        },
+    }
+}
+
+/// Reports the account derived by every version and which of them hold funds.
+/// `account_balances` maps account component addresses to their balance, as fetched from the indexer by the caller.
+pub fn build_key_derivation_report(
+    ecdsa_str: &str,
+    account_balances: &HashMap<String, u64>,
+) -> Result<KeyDerivationReport, WalletLibError> {
+    let mut accounts = Vec::with_capacity(KeyDerivationVersion::ALL.len());
+    for version in KeyDerivationVersion::ALL {
+        let private_key = derive_ristretto_private_key(ecdsa_str, version)?;
+        let public_key = RistrettoPublicKey::from_secret_key(&private_key).to_hex();
+        let account_address = get_account_address_from_public_key(&public_key)?.to_string();
+        let balance = account_balances.get(&account_address).copied().unwrap_or(0);
+        accounts.push(KeyDerivationAccount {
+            version,
+            public_key,
+            account_address,
+            balance,
+        });
+    }
+
+    let funded_versions: Vec<KeyDerivationVersion> = accounts
+        .iter()
+        .filter(|a| a.balance > 0)
+        .map(|a| a.version)
+        .collect();
+    // Prefer the newest funded version, so a user who already migrated is not sent back to the old keys
+    let recommended_version = funded_versions.last().copied().unwrap_or(KeyDerivationVersion::LATEST);
+
+    Ok(KeyDerivationReport {
+        accounts,
+        funded_versions,
+        recommended_version,
+    })
+}
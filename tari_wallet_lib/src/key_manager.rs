@@ -0,0 +1,99 @@
+//! Key derivation compatible with the key manager of the Tari DAN wallet daemon.
+//! The same root secret produces the same keys, and therefore the same accounts, in the snap and in the daemon.
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use tari_crypto::hash_domain;
+use tari_crypto::hashing::DomainSeparatedHasher;
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::{from_hex, Hex};
+use tari_crypto::tari_utilities::ByteArrayError;
+use tari_template_lib::prelude::ComponentAddress;
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::cipher_seed::CipherSeed;
+use crate::component::account_address_from_public_key;
+use crate::error::WalletLibError;
+use crate::secret_key::SecretKeyHandle;
+
+hash_domain!(KeyManagerDomain, "com.tari.base_layer.key_manager", 1);
+
+const LABEL_DERIVE_KEY: &str = "derive_key";
+
+/// Branch of the account keys
+pub const TRANSACTION_BRANCH: &str = "transaction";
+/// Branch of the view keys of confidential resources
+pub const VIEW_KEY_BRANCH: &str = "view_key";
+
+#[wasm_bindgen]
+pub struct KeyManager {
+    root_secret: Zeroizing<Vec<u8>>,
+}
+
+impl KeyManager {
+    pub fn new(root_secret: &[u8]) -> Self {
+        Self {
+            root_secret: Zeroizing::new(root_secret.to_vec()),
+        }
+    }
+
+    /// The daemon uses the entropy of its cipher seed as the root secret
+    pub fn from_cipher_seed(seed: &CipherSeed) -> Self {
+        Self::new(seed.entropy())
+    }
+
+    pub fn derive_key(&self, branch: &str, index: u64) -> Result<RistrettoSecretKey, ByteArrayError> {
+        let derive_key = DomainSeparatedHasher::<Blake2b<U64>, KeyManagerDomain>::new_with_label(LABEL_DERIVE_KEY)
+            .chain(self.root_secret.as_slice())
+            .chain(branch.as_bytes())
+            .chain(index.to_le_bytes())
+            .finalize();
+        RistrettoSecretKey::from_uniform_bytes(derive_key.as_ref())
+    }
+
+    pub fn derive_public_key(&self, branch: &str, index: u64) -> Result<RistrettoPublicKey, ByteArrayError> {
+        let secret_key = self.derive_key(branch, index)?;
+        Ok(RistrettoPublicKey::from_secret_key(&secret_key))
+    }
+
+    /// Address of the account owned by the key at `index` of the transaction branch
+    pub fn derive_account_address(&self, index: u64) -> Result<ComponentAddress, ByteArrayError> {
+        let public_key = self.derive_public_key(TRANSACTION_BRANCH, index)?;
+        Ok(account_address_from_public_key(&public_key))
+    }
+}
+
+#[wasm_bindgen]
+impl KeyManager {
+    pub fn from_root_secret_hex(root_secret_hex: &str) -> Result<KeyManager, WalletLibError> {
+        let root_secret = Zeroizing::new(
+            from_hex(root_secret_hex).map_err(|e| WalletLibError::invalid_argument("root secret", format!("{:?}", e)))?,
+        );
+        Ok(Self::new(&root_secret))
+    }
+
+    pub fn from_mnemonic(mnemonic: &str, passphrase: Option<String>) -> Result<KeyManager, WalletLibError> {
+        let seed = CipherSeed::from_mnemonic(mnemonic, passphrase.as_deref())?;
+        Ok(Self::from_cipher_seed(&seed))
+    }
+
+    pub fn get_private_key(&self, branch: &str, index: u64) -> Result<SecretKeyHandle, WalletLibError> {
+        SecretKeyHandle::from_key_manager(self, branch, index)
+    }
+
+    pub fn get_public_key(&self, branch: &str, index: u64) -> Result<String, WalletLibError> {
+        let public_key = self
+            .derive_public_key(branch, index)
+            .map_err(WalletLibError::key_derivation_failed)?;
+        Ok(public_key.to_hex())
+    }
+
+    pub fn get_account_component_address(&self, index: u64) -> Result<String, WalletLibError> {
+        let account_address = self
+            .derive_account_address(index)
+            .map_err(WalletLibError::key_derivation_failed)?;
+        Ok(account_address.to_string())
+    }
+}
@@ -0,0 +1,267 @@
+//! Password encrypted keystore, used to back up an account key or move it to another wallet without re-deriving it.
+//! The key is encrypted with XChaCha20-Poly1305 under an Argon2id key, and every other field is authenticated as
+//! associated data, so any modification makes the import fail.
+
+use std::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use blake2::Blake2b;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use digest::consts::U32;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tari_crypto::hashing::DomainSeparatedHasher;
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::{from_hex, to_hex};
+use tari_crypto::tari_utilities::ByteArray;
+use zeroize::Zeroizing;
+
+use crate::crypto::TariSnapKeyDomain;
+use crate::key_derivation::KeyDerivationVersion;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const KDF_ALGORITHM: &str = "argon2id";
+const CIPHER_ALGORITHM: &str = "xchacha20-poly1305";
+const LABEL_PUBLIC_KEY_CHECKSUM: &str = "keystore_public_key_checksum";
+
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+// Upper bound on the cost accepted when importing, so a crafted keystore cannot exhaust the WASM memory
+const MAX_KDF_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 16;
+
+const SALT_BYTES: usize = 16;
+const NONCE_BYTES: usize = 24;
+const ENCRYPTION_KEY_BYTES: usize = 32;
+const CHECKSUM_BYTES: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeystoreError {
+    EmptyPassword,
+    InvalidFormat(String),
+    UnsupportedVersion(u32),
+    KeyDerivation(String),
+    /// The authentication tag did not match, either because of a wrong password or a modified keystore
+    DecryptionFailed,
+    ChecksumMismatch,
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::EmptyPassword => write!(f, "The keystore password cannot be empty"),
+            KeystoreError::InvalidFormat(reason) => write!(f, "Invalid keystore: {}", reason),
+            KeystoreError::UnsupportedVersion(version) => write!(f, "Unsupported keystore version {}", version),
+            KeystoreError::KeyDerivation(reason) => write!(f, "Could not derive the keystore key: {}", reason),
+            KeystoreError::DecryptionFailed => {
+                write!(f, "Could not decrypt the keystore: wrong password or the keystore was modified")
+            },
+            KeystoreError::ChecksumMismatch => {
+                write!(f, "The decrypted key does not match the keystore public key checksum")
+            },
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+/// How the stored key was originally derived, so the importing wallet can keep deriving related keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeystoreDerivation {
+    MetaMask { version: KeyDerivationVersion },
+    KeyManager { branch: String, index: u64 },
+    Unknown,
+}
+
+impl Default for KeystoreDerivation {
+    fn default() -> Self {
+        KeystoreDerivation::Unknown
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreKdf {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreCipher {
+    pub algorithm: String,
+    pub nonce: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub kdf: KeystoreKdf,
+    pub cipher: KeystoreCipher,
+    pub derivation: KeystoreDerivation,
+    pub public_key_checksum: String,
+    pub ciphertext: String,
+}
+
+/// Every field except the ciphertext, authenticated as the AEAD associated data
+#[derive(Serialize)]
+struct KeystoreHeader<'a> {
+    version: u32,
+    kdf: &'a KeystoreKdf,
+    cipher: &'a KeystoreCipher,
+    derivation: &'a KeystoreDerivation,
+    public_key_checksum: &'a str,
+}
+
+impl Keystore {
+    pub fn encrypt(
+        secret_key: &RistrettoSecretKey,
+        password: &str,
+        derivation: KeystoreDerivation,
+    ) -> Result<Self, KeystoreError> {
+        if password.is_empty() {
+            return Err(KeystoreError::EmptyPassword);
+        }
+
+        let mut salt = [0u8; SALT_BYTES];
+        let mut nonce = [0u8; NONCE_BYTES];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let mut keystore = Self {
+            version: KEYSTORE_VERSION,
+            kdf: KeystoreKdf {
+                algorithm: KDF_ALGORITHM.to_string(),
+                memory_kib: KDF_MEMORY_KIB,
+                iterations: KDF_ITERATIONS,
+                parallelism: KDF_PARALLELISM,
+                salt: to_hex(&salt),
+            },
+            cipher: KeystoreCipher {
+                algorithm: CIPHER_ALGORITHM.to_string(),
+                nonce: to_hex(&nonce),
+            },
+            derivation,
+            public_key_checksum: public_key_checksum(&RistrettoPublicKey::from_secret_key(secret_key)),
+            ciphertext: String::new(),
+        };
+
+        let encryption_key = derive_encryption_key(password, &keystore.kdf, &salt)?;
+        let ciphertext = XChaCha20Poly1305::new(encryption_key.as_slice().into())
+            .encrypt(XNonce::from_slice(&nonce), Payload {
+                msg: secret_key.as_bytes(),
+                aad: &keystore.associated_data()?,
+            })
+            .map_err(|_| KeystoreError::InvalidFormat("could not encrypt the key".to_string()))?;
+        keystore.ciphertext = to_hex(&ciphertext);
+
+        Ok(keystore)
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<RistrettoSecretKey, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(self.version));
+        }
+        if self.kdf.algorithm != KDF_ALGORITHM {
+            return Err(KeystoreError::InvalidFormat(format!("unsupported KDF '{}'", self.kdf.algorithm)));
+        }
+        if self.cipher.algorithm != CIPHER_ALGORITHM {
+            return Err(KeystoreError::InvalidFormat(format!(
+                "unsupported cipher '{}'",
+                self.cipher.algorithm
+            )));
+        }
+        if self.kdf.memory_kib > MAX_KDF_MEMORY_KIB || self.kdf.iterations > MAX_KDF_ITERATIONS {
+            return Err(KeystoreError::InvalidFormat("the KDF cost is too high".to_string()));
+        }
+
+        let salt = decode_hex("salt", &self.kdf.salt, Some(SALT_BYTES))?;
+        let nonce = decode_hex("nonce", &self.cipher.nonce, Some(NONCE_BYTES))?;
+        let ciphertext = decode_hex("ciphertext", &self.ciphertext, None)?;
+
+        let encryption_key = derive_encryption_key(password, &self.kdf, &salt)?;
+        let key_bytes = Zeroizing::new(
+            XChaCha20Poly1305::new(encryption_key.as_slice().into())
+                .decrypt(XNonce::from_slice(&nonce), Payload {
+                    msg: &ciphertext,
+                    aad: &self.associated_data()?,
+                })
+                .map_err(|_| KeystoreError::DecryptionFailed)?,
+        );
+        let secret_key = RistrettoSecretKey::from_canonical_bytes(&key_bytes)
+            .map_err(|e| KeystoreError::InvalidFormat(format!("invalid secret key: {:?}", e)))?;
+
+        if public_key_checksum(&RistrettoPublicKey::from_secret_key(&secret_key)) != self.public_key_checksum {
+            return Err(KeystoreError::ChecksumMismatch);
+        }
+
+        Ok(secret_key)
+    }
+
+    pub fn to_json(&self) -> Result<String, KeystoreError> {
+        serde_json::to_string(self).map_err(|e| KeystoreError::InvalidFormat(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, KeystoreError> {
+        serde_json::from_str(json).map_err(|e| KeystoreError::InvalidFormat(e.to_string()))
+    }
+
+    fn associated_data(&self) -> Result<Vec<u8>, KeystoreError> {
+        let header = KeystoreHeader {
+            version: self.version,
+            kdf: &self.kdf,
+            cipher: &self.cipher,
+            derivation: &self.derivation,
+            public_key_checksum: &self.public_key_checksum,
+        };
+        serde_json::to_vec(&header).map_err(|e| KeystoreError::InvalidFormat(e.to_string()))
+    }
+}
+
+/// Short hash of the public key, which lets an importer check which account a keystore holds before decrypting it
+pub fn public_key_checksum(public_key: &RistrettoPublicKey) -> String {
+    let hash = DomainSeparatedHasher::<Blake2b<U32>, TariSnapKeyDomain>::new_with_label(LABEL_PUBLIC_KEY_CHECKSUM)
+        .chain(public_key.as_bytes())
+        .finalize();
+    to_hex(&hash.as_ref()[..CHECKSUM_BYTES])
+}
+
+fn derive_encryption_key(
+    password: &str,
+    kdf: &KeystoreKdf,
+    salt: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, KeystoreError> {
+    let params = Params::new(
+        kdf.memory_kib,
+        kdf.iterations,
+        kdf.parallelism,
+        Some(ENCRYPTION_KEY_BYTES),
+    )
+    .map_err(|e| KeystoreError::KeyDerivation(e.to_string()))?;
+    let mut encryption_key = Zeroizing::new(vec![0u8; ENCRYPTION_KEY_BYTES]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut encryption_key)
+        .map_err(|e| KeystoreError::KeyDerivation(e.to_string()))?;
+    Ok(encryption_key)
+}
+
+fn decode_hex(field: &str, value: &str, expected_len: Option<usize>) -> Result<Vec<u8>, KeystoreError> {
+    let bytes = from_hex(value).map_err(|_| KeystoreError::InvalidFormat(format!("invalid {} encoding", field)))?;
+    if let Some(len) = expected_len {
+        if bytes.len() != len {
+            return Err(KeystoreError::InvalidFormat(format!(
+                "expected a {} byte {} but got {}",
+                len,
+                field,
+                bytes.len()
+            )));
+        }
+    }
+    Ok(bytes)
+}
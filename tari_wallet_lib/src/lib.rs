@@ -1,142 +1,414 @@
 pub mod component;
-mod crypto;
+pub mod crypto;
+pub mod error;
 pub mod metadata;
-mod confidential_transfer;
+pub mod confidential_transfer;
+pub mod coin_selection;
+pub mod key_derivation;
+pub mod cipher_seed;
+pub mod key_manager;
+pub mod secret_key;
+pub mod message_signing;
+pub mod sign_in;
+pub mod keystore;
+pub mod transaction_builder;
+pub mod arg_encoding;
+pub mod abi_encoding;
+pub mod transaction_summary;
+pub mod substate_requirements;
+pub mod fee_estimation;
+pub mod batch_transfer;
+pub mod epoch_window;
+pub mod fee_sponsorship;
+pub mod nft_transfer;
+pub mod account_nft;
+pub mod fungible_token;
 
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use component::get_account_address_from_public_key;
-use confidential_transfer::{build_confidential_transfer_transaction, ConfidentialTransferInputSelection, ConfidentialTransferParams};
-use crypto::AlwaysMissLookupTable;
-use tari_crypto::keys::{PublicKey, SecretKey};
-use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use abi_encoding::{build_call_function_instruction, build_call_method_instruction};
+use account_nft::build_account_nft_mint;
+use arg_encoding::{encode_typed_args, TypedArg};
+use batch_transfer::{BatchTransfer, TransferItem};
+use cipher_seed::CipherSeed;
+use coin_selection::CoinSelectionStrategy;
+use component::{
+    account_nft_address_from_public_key, get_account_address_from_public_key, owner_token_from_public_key,
+};
+use confidential_transfer::{
+    build_confidential_transfer_transaction, parse_output_key_indexes, ConfidentialTransferInputSelection,
+    ConfidentialTransferParams, OutputKeyIndex,
+};
+use crypto::{BabyStepGiantStepTable, DEFAULT_LOOKUP_TABLE_SIZE};
+use epoch_window::{EpochWindow, DEFAULT_VALIDITY_EPOCHS};
+use error::{from_js_value, to_js_value, WalletLibError};
+use fee_estimation::{rebuild_with_fee, FeeMargin};
+use fee_sponsorship::{build_sponsored_transaction, transaction_signers, SponsorPolicy};
+use fungible_token::{created_resource_address, FungibleToken, FungibleTokenParams};
+use key_derivation::{build_key_derivation_report, derive_ristretto_private_key, KeyDerivationVersion};
+use key_manager::{KeyManager, TRANSACTION_BRANCH};
+use keystore::{Keystore, KeystoreDerivation};
+use metadata::MetadataField;
+use nft_transfer::NftTransfer;
+use secret_key::SecretKeyHandle;
+use sign_in::SignInMessage;
+use substate_requirements::infer_substate_requirements;
+use transaction_summary::{parse_transaction_json, InstructionsInput};
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_crypto::tari_utilities::hex::Hex;
-use tari_crypto::tari_utilities::ByteArray;
-use tari_engine_types::confidential::ElgamalVerifiableBalance;
+use tari_engine_types::commit_result::FinalizeResult;
 use tari_engine_types::instruction::Instruction;
 use tari_engine_types::resource::Resource;
 use tari_engine_types::substate::SubstateId;
 use tari_engine_types::vault::Vault;
 use tari_template_lib::args;
 use tari_template_lib::constants::{XTR_FAUCET_COMPONENT_ADDRESS, XTR_FAUCET_VAULT_ADDRESS};
-use tari_template_lib::models::VaultId;
-use tari_template_lib::prelude::{
-    Amount, NonFungibleAddress, ResourceAddress, RistrettoPublicKeyBytes, NonFungibleId,
-};
+use tari_template_lib::models::{TemplateAddress, VaultId};
+use tari_template_lib::prelude::{Amount, ComponentAddress, NonFungibleId, ResourceAddress};
+use tari_template_abi::TemplateDef;
 use tari_transaction::{SubstateRequirement, Transaction};
 use wasm_bindgen::prelude::*;
 
-fn ecdsa_to_ristretto_private_key(ecdsa_str: &str) -> Result<RistrettoSecretKey, JsError> {
-    let no_prefix_hex = ecdsa_str.strip_prefix("0x").unwrap_or(ecdsa_str);
-    RistrettoSecretKey::from_uniform_bytes(no_prefix_hex.as_bytes())
-        .map_err(|e| JsError::new(&format!("Could not parse private key: {:?}", e)))
-}
-
 #[wasm_bindgen]
-pub fn build_ristretto_private_key(ecdsa_str: &str) -> Result<String, JsError> {
-    let private_key = ecdsa_to_ristretto_private_key(ecdsa_str)?;
-    Ok(private_key.to_hex())
+pub fn build_ristretto_private_key(
+    ecdsa_str: &str,
+    version: Option<KeyDerivationVersion>,
+) -> Result<SecretKeyHandle, WalletLibError> {
+    SecretKeyHandle::from_metamask_key(ecdsa_str, version)
 }
 
 #[wasm_bindgen]
-pub fn build_ristretto_public_key(ecdsa_str: &str) -> Result<String, JsError> {
-    let private_key = ecdsa_to_ristretto_private_key(ecdsa_str)?;
+pub fn build_ristretto_public_key(
+    ecdsa_str: &str,
+    version: Option<KeyDerivationVersion>,
+) -> Result<String, WalletLibError> {
+    let private_key = derive_ristretto_private_key(ecdsa_str, version.unwrap_or_default())?;
     let public_key = RistrettoPublicKey::from_secret_key(&private_key);
     Ok(public_key.to_hex())
 }
 
+#[derive(serde::Serialize)]
+struct CipherSeedInfo {
+    version: u8,
+    birthday: u16,
+}
+
+/// Validates a Tari CipherSeed mnemonic, returning its version and birthday
+#[wasm_bindgen]
+pub fn validate_cipher_seed(mnemonic: &str, passphrase: Option<String>) -> Result<JsValue, WalletLibError> {
+    let seed = CipherSeed::from_mnemonic(mnemonic, passphrase.as_deref())?;
+    let info = CipherSeedInfo {
+        version: seed.version(),
+        birthday: seed.birthday(),
+    };
+    to_js_value(&info)
+}
+
+/// Derives the account key at `index` from a Tari CipherSeed mnemonic, matching the keys of the Tari wallets
+#[wasm_bindgen]
+pub fn build_ristretto_private_key_from_cipher_seed(
+    mnemonic: &str,
+    passphrase: Option<String>,
+    index: u64,
+) -> Result<SecretKeyHandle, WalletLibError> {
+    let key_manager = KeyManager::from_mnemonic(mnemonic, passphrase)?;
+    key_manager.get_private_key(TRANSACTION_BRANCH, index)
+}
+
+#[wasm_bindgen]
+pub fn build_ristretto_public_key_from_cipher_seed(
+    mnemonic: &str,
+    passphrase: Option<String>,
+    index: u64,
+) -> Result<String, WalletLibError> {
+    let key_manager = KeyManager::from_mnemonic(mnemonic, passphrase)?;
+    key_manager.get_public_key(TRANSACTION_BRANCH, index)
+}
+
+/// Reports which key derivation version holds funds, to migrate accounts created with an older version.
+/// `account_balances_js` maps the account component addresses of every version to their balance.
+#[wasm_bindgen]
+pub fn get_key_derivation_report(ecdsa_str: &str, account_balances_js: JsValue) -> Result<JsValue, WalletLibError> {
+    let account_balances: Option<HashMap<String, u64>> = from_js_value("account_balances", account_balances_js)?;
+    let report = build_key_derivation_report(ecdsa_str, &account_balances.unwrap_or_default())?;
+    to_js_value(&report)
+}
+
+/// Signs an arbitrary message, bound to `domain` (e.g. the origin of the website requesting the signature)
+#[wasm_bindgen]
+pub fn sign_message(secret_key: &SecretKeyHandle, message: &[u8], domain: &str) -> Result<String, WalletLibError> {
+    let signature = message_signing::sign_message(secret_key.secret_key(), message, domain)?;
+    Ok(message_signing::signature_to_hex(&signature))
+}
+
+#[wasm_bindgen]
+pub fn verify_message_signature(
+    public_key_hex: &str,
+    message: &[u8],
+    signature_hex: &str,
+    domain: &str,
+) -> Result<bool, WalletLibError> {
+    let public_key = RistrettoPublicKey::from_hex(public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+    let signature = message_signing::signature_from_hex(signature_hex)?;
+    Ok(message_signing::verify_message_signature(&public_key, message, &signature, domain)?)
+}
+
+/// Encrypts the key with the password. `derivation_js` optionally records how the key was derived.
+#[wasm_bindgen]
+pub fn export_keystore(
+    secret_key: &SecretKeyHandle,
+    password: &str,
+    derivation_js: JsValue,
+) -> Result<String, WalletLibError> {
+    let derivation: Option<KeystoreDerivation> = from_js_value("derivation", derivation_js)?;
+    let keystore = Keystore::encrypt(secret_key.secret_key(), password, derivation.unwrap_or_default())?;
+    Ok(keystore.to_json()?)
+}
+
+#[wasm_bindgen]
+pub fn import_keystore(keystore_json: &str, password: &str) -> Result<SecretKeyHandle, WalletLibError> {
+    let keystore = Keystore::from_json(keystore_json)?;
+    let secret_key = keystore.decrypt(password)?;
+    Ok(SecretKeyHandle::new(secret_key))
+}
+
 #[wasm_bindgen]
-pub fn get_account_component_address(public_key: &str) -> Result<String, JsError> {
+pub fn create_sign_in_message(
+    domain: &str,
+    public_key_hex: &str,
+    nonce: &str,
+    issued_at: u64,
+    expiration_time: u64,
+    statement: Option<String>,
+) -> Result<String, WalletLibError> {
+    let public_key = RistrettoPublicKey::from_hex(public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+    let mut builder = SignInMessage::builder()
+        .with_domain(domain)
+        .with_public_key(public_key)
+        .with_nonce(nonce)
+        .with_issued_at(issued_at)
+        .with_expiration_time(expiration_time);
+    if let Some(statement) = statement {
+        builder = builder.with_statement(statement);
+    }
+    Ok(builder.build()?.to_string())
+}
+
+/// Signs a canonical sign-in message, bound to the domain the message names
+#[wasm_bindgen]
+pub fn sign_sign_in_message(secret_key: &SecretKeyHandle, message: &str) -> Result<String, WalletLibError> {
+    let message = SignInMessage::from_str(message)?;
+    let signature = message.sign(secret_key.secret_key())?;
+    Ok(message_signing::signature_to_hex(&signature))
+}
+
+#[wasm_bindgen]
+pub fn get_account_component_address(public_key: &str) -> Result<String, WalletLibError> {
     let account_address = get_account_address_from_public_key(&public_key)?;
     Ok(account_address.to_string())
 }
 
 #[wasm_bindgen]
-pub fn get_owner_token(public_key_hex: &str) -> Result<JsValue, JsError> {
-    let public_key = RistrettoPublicKey::from_hex(public_key_hex)
-        .map_err(|e| JsError::new(&format!("Could not parse public key: {:?}", e)))?;
-    let owner_token = NonFungibleAddress::from_public_key(
-        RistrettoPublicKeyBytes::from_bytes(public_key.as_bytes()).unwrap(),
-    );
-    let encoded_token = tari_bor::encode(&owner_token)?;
+pub fn get_owner_token(public_key_hex: &str) -> Result<JsValue, WalletLibError> {
+    let public_key = RistrettoPublicKey::from_hex(public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+    let owner_token = owner_token_from_public_key(&public_key)?;
+    let encoded_token = tari_bor::encode(&owner_token).map_err(WalletLibError::encoding_failed)?;
+
+    to_js_value(&encoded_token)
+}
 
-    Ok(serde_wasm_bindgen::to_value(&encoded_token)?)
+/// Encodes instruction arguments from their typed description, e.g. `[{ "type": "Amount", "value": 100 }]`.
+/// The result can be passed as the `args` of any instruction.
+#[wasm_bindgen]
+pub fn encode_args(args_js: JsValue) -> Result<JsValue, WalletLibError> {
+    let args: Vec<TypedArg> = from_js_value("args", args_js)?;
+    to_js_value(&encode_typed_args(args)?)
+}
+
+/// Builds a `CallFunction` instruction, type checking the plain JSON `values_js` against the template definition
+/// returned by the indexer. Buckets and proofs are passed from the workspace as `{ "workspace": "name" }`.
+#[wasm_bindgen]
+pub fn encode_call_function(
+    template_address: &str,
+    template_def_js: JsValue,
+    function: &str,
+    values_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let template_address = parse_template_address_arg(template_address)?;
+    let template_def: TemplateDef = from_js_value("template_def", template_def_js)?;
+    let values: Vec<serde_json::Value> = from_js_value("values", values_js)?;
+    let instruction = build_call_function_instruction(template_address, &template_def, function, values)?;
+    to_js_value(&instruction)
 }
 
+/// Same as `encode_call_function`, for a method of an existing component
 #[wasm_bindgen]
-pub fn encode_metadata(metadata_js: JsValue) -> Result<JsValue, JsError> {
+pub fn encode_call_method(
+    component_address: &str,
+    template_def_js: JsValue,
+    method: &str,
+    values_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let component_address = parse_component_address_arg(component_address)?;
+    let template_def: TemplateDef = from_js_value("template_def", template_def_js)?;
+    let values: Vec<serde_json::Value> = from_js_value("values", values_js)?;
+    let instruction = build_call_method_instruction(component_address, &template_def, method, values)?;
+    to_js_value(&instruction)
+}
+
+#[wasm_bindgen]
+pub fn encode_metadata(metadata_js: JsValue) -> Result<JsValue, WalletLibError> {
     metadata::encode_metadata(metadata_js)
 }
 
 #[wasm_bindgen]
-pub fn parse_resource_address(resource_address_str: &str) -> Result<JsValue, JsError> {
-    let resource_address =  ResourceAddress::from_str(resource_address_str)?;
-    Ok(serde_wasm_bindgen::to_value(&resource_address)?)
+pub fn parse_resource_address(resource_address_str: &str) -> Result<JsValue, WalletLibError> {
+    let resource_address = parse_resource_address_arg(resource_address_str)?;
+    to_js_value(&resource_address)
 }
 
 #[wasm_bindgen]
-pub fn encode_non_fungible_id(id_str: &str) -> Result<JsValue, JsError> {
+pub fn encode_non_fungible_id(id_str: &str) -> Result<JsValue, WalletLibError> {
     let id =  NonFungibleId::try_from_canonical_string(id_str)
-        .map_err(|_| JsError::new("Invalid NonFungibleId String"))?;
-    let encoded_id = tari_bor::encode(&id)?;
-    Ok(serde_wasm_bindgen::to_value(&encoded_id)?)
+        .map_err(|e| WalletLibError::invalid_argument("non fungible id", format!("{:?}", e)))?;
+    let encoded_id = tari_bor::encode(&id).map_err(WalletLibError::encoding_failed)?;
+    to_js_value(&encoded_id)
 }
 
 #[wasm_bindgen]
-pub fn encode_amount(amount: i64) -> Result<JsValue, JsError> {
+pub fn encode_amount(amount: i64) -> Result<JsValue, WalletLibError> {
     let amount = Amount::new(amount);
-    let encoded_amount = tari_bor::encode(&amount)?;
-    Ok(serde_wasm_bindgen::to_value(&encoded_amount)?)
+    let encoded_amount = tari_bor::encode(&amount).map_err(WalletLibError::encoding_failed)?;
+    to_js_value(&encoded_amount)
+}
+
+/// Describes each step of a transaction for the confirmation dialogs.
+/// `transaction_js` is either a transaction JSON string, as returned by the builders, or the instructions of a
+/// transaction (a list, or an object with `instructions` and `fee_instructions`).
+/// Steps that use the account of `signer_public_key_hex` are marked, so they can be highlighted.
+#[wasm_bindgen]
+pub fn summarize_transaction(
+    transaction_js: JsValue,
+    signer_public_key_hex: Option<String>,
+) -> Result<JsValue, WalletLibError> {
+    let signer_public_key = signer_public_key_hex
+        .map(|pk| RistrettoPublicKey::from_hex(&pk))
+        .transpose()
+        .map_err(WalletLibError::invalid_public_key)?;
+    let summary = parse_instructions_arg(transaction_js)?.summarize(signer_public_key.as_ref());
+    to_js_value(&summary)
+}
+
+/// Returns the substates that must be passed as `required_substates` or inputs of a transaction: the components it
+/// calls and the resources, badges and vaults in its arguments, except for the accounts it creates.
+/// `transaction_js` is given in any of the forms accepted by `summarize_transaction`.
+/// `created_components_js` lists the addresses of the components created by the transaction, which are excluded too.
+#[wasm_bindgen]
+pub fn infer_required_substates(
+    transaction_js: JsValue,
+    created_components_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let instructions = parse_instructions_arg(transaction_js)?;
+    let created_components: Option<Vec<String>> = from_js_value("created_components", created_components_js)?;
+    let created_components = created_components
+        .unwrap_or_default()
+        .iter()
+        .map(|address| parse_component_address_arg(address))
+        .collect::<Result<Vec<_>, _>>()?;
+    let requirements = infer_substate_requirements(
+        instructions.fee_instructions(),
+        instructions.instructions(),
+        &created_components,
+    );
+    to_js_value(&requirements)
+}
+
+/// Builds the epoch window to pass to the transaction builders from the current epoch reported by the indexer.
+/// The transaction is valid for `validity_epochs` epochs, or `DEFAULT_VALIDITY_EPOCHS` if not given.
+#[wasm_bindgen]
+pub fn epoch_window_from_current_epoch(
+    current_epoch: u64,
+    validity_epochs: Option<u64>,
+) -> Result<JsValue, WalletLibError> {
+    let epoch_window =
+        EpochWindow::from_current_epoch(current_epoch, validity_epochs.unwrap_or(DEFAULT_VALIDITY_EPOCHS));
+    to_js_value(&epoch_window)
+}
+
+/// Estimates the fee of a transaction from its dry run. `finalize_result_js` is the `finalize` field of the
+/// execution result returned by a dry run `submit_transaction`, and `margin_js` optionally overrides the default
+/// safety margin, e.g. `{ "percent": 10, "minimum": 50 }`.
+#[wasm_bindgen]
+pub fn estimate_fee(finalize_result_js: JsValue, margin_js: JsValue) -> Result<JsValue, WalletLibError> {
+    let finalize_result: FinalizeResult = from_js_value("finalize_result", finalize_result_js)?;
+    let margin: Option<FeeMargin> = from_js_value("margin", margin_js)?;
+    let estimate = fee_estimation::estimate_fee(&finalize_result, &margin.unwrap_or_default())?;
+    to_js_value(&estimate)
+}
+
+/// Rebuilds a transaction returned by any of the builders with a new `pay_fee` amount and signs it again,
+/// e.g. with the fee returned by `estimate_fee`
+#[wasm_bindgen]
+pub fn rebuild_transaction_with_fee(
+    account_private_key: &SecretKeyHandle,
+    transaction_json: &str,
+    fee: i64,
+) -> Result<JsValue, WalletLibError> {
+    let transaction = parse_transaction_json(transaction_json)?;
+    let transaction = rebuild_with_fee(&transaction, fee, account_private_key.secret_key())?;
+    encode_transaction(&transaction)
 }
 
 #[wasm_bindgen]
 pub fn create_transaction(
-    account_private_key_hex: &str,
+    account_private_key: &SecretKeyHandle,
     instructions_js: JsValue,
     fee_instructions_js: JsValue,
     input_refs_js: JsValue,
-) -> Result<JsValue, JsError> {
-    let account_private_key = RistrettoSecretKey::from_hex(account_private_key_hex)
-        .map_err(|e| JsError::new(&format!("Could not parse private key: {:?}", e)))?;
-    let fee_instructions: Vec<Instruction> = serde_wasm_bindgen::from_value(fee_instructions_js)?;
-    let instructions: Vec<Instruction> = serde_wasm_bindgen::from_value(instructions_js)?;
-    let input_refs: Vec<SubstateRequirement> = serde_wasm_bindgen::from_value(input_refs_js)?;
-
-    let transaction = Transaction::builder()
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let account_private_key = account_private_key.secret_key();
+    let fee_instructions: Vec<Instruction> = from_js_value("fee_instructions", fee_instructions_js)?;
+    let instructions: Vec<Instruction> = from_js_value("instructions", instructions_js)?;
+    let input_refs: Vec<SubstateRequirement> = from_js_value("input_refs", input_refs_js)?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+
+    let builder = Transaction::builder()
         .with_fee_instructions(fee_instructions.to_vec())
         .with_instructions(instructions.to_vec())
-        .with_inputs(input_refs.to_vec())
-        .sign(&account_private_key)
-        .build();
+        .with_inputs(input_refs.to_vec());
+    let transaction = epoch_window.apply(builder).sign(account_private_key).build();
 
     encode_transaction(&transaction)
 }
 
 #[wasm_bindgen]
 pub fn create_transfer_transaction(
-    source_private_key: &str,
+    source_private_key: &SecretKeyHandle,
     destination_public_key_hex: &str,
     create_destination_account: bool,
     resource_address: &str,
     amount: i64,
     fee: i64,
-) -> Result<JsValue, JsError> {
-    let source_private_key = RistrettoSecretKey::from_hex(source_private_key)
-        .map_err(|e| JsError::new(&format!("Could not parse private key: {:?}", e)))?;
-    let source_public_key = RistrettoPublicKey::from_secret_key(&source_private_key);
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let source_private_key = source_private_key.secret_key();
+    let source_public_key = RistrettoPublicKey::from_secret_key(source_private_key);
     let source_account_address = get_account_address_from_public_key(&source_public_key.to_hex())?;
 
     let destination_account_address = get_account_address_from_public_key(destination_public_key_hex)?;
-    let destination_public_key = RistrettoPublicKey::from_hex(destination_public_key_hex)
-        .map_err(|e| JsError::new(&format!("Could not parse public key: {:?}", e)))?;
+    let destination_public_key =
+        RistrettoPublicKey::from_hex(destination_public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
 
     let mut instructions = vec![
         Instruction::CallMethod {
             component_address: source_account_address,
             method: "withdraw".to_string(),
             args: args![
-                ResourceAddress::from_str(resource_address)?,
+                parse_resource_address_arg(resource_address)?,
                 Amount::new(amount)
             ],
         },
@@ -163,23 +435,292 @@ pub fn create_transfer_transaction(
         args: args![Amount::new(fee)],
     });
 
-    let resource_address_obj = ResourceAddress::from_str(resource_address)?;
+    let resource_address_obj = parse_resource_address_arg(resource_address)?;
     let resource_substate = SubstateId::Resource(resource_address_obj);
     let resource_shard_id = SubstateRequirement::new(resource_substate, None);
     let input_refs = vec![resource_shard_id];
 
-    let transaction = Transaction::builder()
+    let builder = Transaction::builder()
         .with_fee_instructions(instructions.to_vec())
-        .with_inputs(input_refs)
-        .sign(&source_private_key)
-        .build();
+        .with_inputs(input_refs);
+    let transaction = epoch_window.apply(builder).sign(source_private_key).build();
 
     encode_transaction(&transaction)
 }
 
+/// Mints an NFT with `metadata_js` (a list of `{ key, value }`, as in `encode_metadata`) into the account of the
+/// signer, creating its account NFT component in the same transaction when `create_component` is set.
+/// Returns the predicted address of the account NFT component along with the transaction.
+#[wasm_bindgen]
+pub fn create_account_nft_mint_transaction(
+    account_private_key: &SecretKeyHandle,
+    create_component: bool,
+    metadata_js: JsValue,
+    fee: i64,
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    if fee <= 0 {
+        return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+    }
+    let account_private_key = account_private_key.secret_key();
+    let account_public_key = RistrettoPublicKey::from_secret_key(account_private_key);
+    let account_address = get_account_address_from_public_key(&account_public_key.to_hex())?;
+    let metadata: Vec<MetadataField> = from_js_value("metadata", metadata_js)?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+
+    let mint = build_account_nft_mint(&account_public_key, create_component, metadata)?;
+    let fee_instructions = vec![Instruction::CallMethod {
+        component_address: account_address,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(fee)],
+    }];
+    let builder = Transaction::builder()
+        .with_fee_instructions(fee_instructions)
+        .with_instructions(mint.instructions);
+    let transaction = epoch_window.apply(builder).sign(account_private_key).build();
+
+    let encoded = EncodedTransaction::new(&transaction)?;
+    to_js_value(&EncodedAccountNftTransaction {
+        transaction: encoded.transaction,
+        component_address: mint.component_address.to_string(),
+    })
+}
+
+/// Address of the account NFT component of the owner of `public_key`, whether it exists or not
+#[wasm_bindgen]
+pub fn get_account_nft_component_address(public_key: &str) -> Result<String, WalletLibError> {
+    let public_key = RistrettoPublicKey::from_hex(public_key).map_err(WalletLibError::invalid_public_key)?;
+    Ok(account_nft_address_from_public_key(&public_key).to_string())
+}
+
+/// Creates a new fungible resource with the token template (`templates/fungible_token`) published at
+/// `template_address` and deposits its initial supply into the account of the signer. `token_js` is `{ symbol,
+/// divisibility, initial_supply, metadata, owner_rule, confidential, view_key }`, where `metadata` is a list of
+/// `{ key, value }` as in `encode_metadata`, `owner_rule` defaults to `"OwnedBySigner"` and `view_key` is an optional
+/// hex public key for confidential resources.
+/// The address of the new resource is only known after execution, see `get_created_resource_address`.
+#[wasm_bindgen]
+pub fn create_fungible_token_transaction(
+    account_private_key: &SecretKeyHandle,
+    template_address: &str,
+    token_js: JsValue,
+    fee: i64,
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    if fee <= 0 {
+        return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+    }
+    let account_private_key = account_private_key.secret_key();
+    let account_public_key = RistrettoPublicKey::from_secret_key(account_private_key);
+    let account_address = get_account_address_from_public_key(&account_public_key.to_hex())?;
+    let params: FungibleTokenParams = from_js_value("token", token_js)?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+
+    let token = FungibleToken::new(params)?;
+    let fee_instructions = vec![Instruction::CallMethod {
+        component_address: account_address,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(fee)],
+    }];
+    let builder = Transaction::builder()
+        .with_fee_instructions(fee_instructions)
+        .with_instructions(token.instructions(parse_template_address_arg(template_address)?, account_address)?);
+    let transaction = epoch_window.apply(builder).sign(account_private_key).build();
+
+    encode_transaction(&transaction)
+}
+
+/// Address of the fungible resource created by a transaction. `finalize_result_js` is the `finalize` field of the
+/// execution result returned by a dry run `submit_transaction` of the transaction.
+#[wasm_bindgen]
+pub fn get_created_resource_address(finalize_result_js: JsValue) -> Result<String, WalletLibError> {
+    let finalize_result: FinalizeResult = from_js_value("finalize_result", finalize_result_js)?;
+    Ok(created_resource_address(&finalize_result)?.to_string())
+}
+
+/// Transfers the non fungible tokens `ids_js` (a list of ids in their canonical string form, e.g. `"uuid:..."`) of
+/// `resource_address` to the account of `destination_public_key_hex`
+#[wasm_bindgen]
+pub fn create_nft_transfer_transaction(
+    source_private_key: &SecretKeyHandle,
+    destination_public_key_hex: &str,
+    create_destination_account: bool,
+    resource_address: &str,
+    ids_js: JsValue,
+    fee: i64,
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    if fee <= 0 {
+        return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+    }
+    let source_private_key = source_private_key.secret_key();
+    let source_public_key = RistrettoPublicKey::from_secret_key(source_private_key);
+    let source_account_address = get_account_address_from_public_key(&source_public_key.to_hex())?;
+
+    let destination_public_key =
+        RistrettoPublicKey::from_hex(destination_public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+    let ids: Vec<String> = from_js_value("ids", ids_js)?;
+    let ids = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            NonFungibleId::try_from_canonical_string(id)
+                .map_err(|e| WalletLibError::invalid_argument(&format!("ids[{}]", i), format!("{:?}", e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+
+    let transfer = NftTransfer::new(
+        source_account_address,
+        destination_public_key,
+        create_destination_account,
+        parse_resource_address_arg(resource_address)?,
+        ids,
+    )?;
+
+    let fee_instructions = vec![Instruction::CallMethod {
+        component_address: source_account_address,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(fee)],
+    }];
+    let builder = Transaction::builder()
+        .with_fee_instructions(fee_instructions)
+        .with_instructions(transfer.instructions())
+        .with_inputs(transfer.inputs());
+    let transaction = epoch_window.apply(builder).sign(source_private_key).build();
+
+    encode_transaction(&transaction)
+}
+
+/// Transfers to several recipients in one transaction paying a single fee.
+/// `transfers_js` is a list of `{ destination_public_key, resource_address, amount, create_account }`, where transfers
+/// to the same recipient and resource are merged. Each resource is withdrawn once and split between the recipients by
+/// the bucket split template (`templates/bucket_split`) published at `split_template_address`. When `balances_js` maps
+/// resource addresses to the balances of the source account, the total of each resource is checked against them,
+/// with the fee counted in the XTR total.
+#[wasm_bindgen]
+pub fn create_batch_transfer_transaction(
+    source_private_key: &SecretKeyHandle,
+    split_template_address: &str,
+    transfers_js: JsValue,
+    fee: i64,
+    balances_js: JsValue,
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    if fee <= 0 {
+        return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+    }
+    let source_private_key = source_private_key.secret_key();
+    let source_public_key = RistrettoPublicKey::from_secret_key(source_private_key);
+    let source_account_address = get_account_address_from_public_key(&source_public_key.to_hex())?;
+    let split_template_address = parse_template_address_arg(split_template_address)?;
+
+    let transfers: Vec<TransferItem> = from_js_value("transfers", transfers_js)?;
+    let balances: Option<HashMap<String, i64>> = from_js_value("balances", balances_js)?;
+    let batch = BatchTransfer::new(&source_public_key, transfers)?;
+    if let Some(balances) = balances {
+        let balances = balances
+            .into_iter()
+            .map(|(resource_address, balance)| Ok((parse_resource_address_arg(&resource_address)?, balance)))
+            .collect::<Result<HashMap<_, _>, WalletLibError>>()?;
+        batch.check_balances(&balances, fee)?;
+    }
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+
+    let fee_instructions = vec![Instruction::CallMethod {
+        component_address: source_account_address,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(fee)],
+    }];
+    let input_refs = batch
+        .resource_totals()
+        .iter()
+        .map(|(resource_address, _)| SubstateRequirement::new(SubstateId::Resource(*resource_address), None))
+        .collect::<Vec<_>>();
+
+    let builder = Transaction::builder()
+        .with_fee_instructions(fee_instructions)
+        .with_instructions(batch.instructions(source_account_address, split_template_address))
+        .with_inputs(input_refs);
+    let transaction = epoch_window.apply(builder).sign(source_private_key).build();
+
+    encode_transaction(&transaction)
+}
+
+/// Builds a transaction signed by the user whose fee is paid by the account of `sponsor_public_key_hex`.
+/// The returned transaction is partially signed: it must be handed to the sponsor to be signed with
+/// `sponsor_transaction` before it can be submitted.
+#[wasm_bindgen]
+pub fn create_sponsored_transaction(
+    user_private_key: &SecretKeyHandle,
+    sponsor_public_key_hex: &str,
+    fee: i64,
+    instructions_js: JsValue,
+    input_refs_js: JsValue,
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let sponsor_public_key =
+        RistrettoPublicKey::from_hex(sponsor_public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+    let instructions: Vec<Instruction> = from_js_value("instructions", instructions_js)?;
+    let input_refs: Vec<SubstateRequirement> = from_js_value("input_refs", input_refs_js)?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+
+    let transaction = build_sponsored_transaction(
+        user_private_key.secret_key(),
+        &sponsor_public_key,
+        fee,
+        instructions,
+        input_refs,
+        epoch_window,
+    )?;
+    encode_transaction(&transaction)
+}
+
+/// Adds the signature of the sponsor to a transaction created with `create_sponsored_transaction`, after checking
+/// its signatures and that the sponsor only pays its fee, up to `max_fee` if given.
+/// Besides the accounts of the other signers, the instructions can only call the components of
+/// `allowed_components_js` and the functions of the templates of `allowed_templates_js`, both optional lists.
+#[wasm_bindgen]
+pub fn sponsor_transaction(
+    sponsor_private_key: &SecretKeyHandle,
+    transaction_json: &str,
+    max_fee: Option<i64>,
+    allowed_components_js: JsValue,
+    allowed_templates_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let transaction = parse_transaction_json(transaction_json)?;
+    let allowed_components: Option<Vec<String>> = from_js_value("allowed_components", allowed_components_js)?;
+    let allowed_templates: Option<Vec<String>> = from_js_value("allowed_templates", allowed_templates_js)?;
+    let policy = SponsorPolicy {
+        max_fee,
+        allowed_components: allowed_components
+            .unwrap_or_default()
+            .iter()
+            .map(|address| parse_component_address_arg(address))
+            .collect::<Result<_, _>>()?,
+        allowed_templates: allowed_templates
+            .unwrap_or_default()
+            .iter()
+            .map(|address| parse_template_address_arg(address))
+            .collect::<Result<_, _>>()?,
+    };
+    let transaction = fee_sponsorship::sponsor_transaction(transaction, sponsor_private_key.secret_key(), &policy)?;
+    encode_transaction(&transaction)
+}
+
+/// Returns the public keys that have signed the transaction, e.g. to check a partially signed transaction
+#[wasm_bindgen]
+pub fn get_transaction_signers(transaction_json: &str) -> Result<JsValue, WalletLibError> {
+    let transaction = parse_transaction_json(transaction_json)?;
+    let signers: Vec<String> = transaction_signers(&transaction).iter().map(|pk| pk.to_hex()).collect();
+    to_js_value(&signers)
+}
+
+/// `output_key_indexes_js` maps the hex commitment of each change output of the source account to its key index as a
+/// decimal string. The result holds the key index of the new change output, which the caller must add to that map.
 #[wasm_bindgen]
 pub fn create_confidential_transfer_transaction(
-    source_private_key: &str,
+    source_private_key: &SecretKeyHandle,
     source_vault_id: &str,
     source_vault_js: JsValue,
     destination_public_key_hex: &str,
@@ -191,26 +732,36 @@ pub fn create_confidential_transfer_transaction(
     proof_from_resource: Option<String>,
     output_to_revealed: bool,
     input_selection_js: JsValue,
-) -> Result<JsValue, JsError> {
-    let source_private_key = RistrettoSecretKey::from_hex(source_private_key)
-        .map_err(|e| JsError::new(&format!("Could not parse private key: {:?}", e)))?;
-    let source_public_key = RistrettoPublicKey::from_secret_key(&source_private_key);
+    coin_selection_js: JsValue,
+    output_key_indexes_js: JsValue,
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let source_private_key = source_private_key.secret_key();
+    let source_public_key = RistrettoPublicKey::from_secret_key(source_private_key);
     let source_account_address = get_account_address_from_public_key(&source_public_key.to_hex())?;
 
     let destination_account_address = get_account_address_from_public_key(destination_public_key_hex)?;
-    let destination_public_key = RistrettoPublicKey::from_hex(destination_public_key_hex)
-        .map_err(|e| JsError::new(&format!("Could not parse public key: {:?}", e)))?;
-
-    let resource_address = ResourceAddress::from_str(resource_address)?;
-    let proof_from_resource = proof_from_resource.map(|s| ResourceAddress::from_str(&s)).transpose()?;
-
-    let source_vault_id = VaultId::from_str(source_vault_id)?;
-    let source_vault: Vault = serde_wasm_bindgen::from_value(source_vault_js)?;
-    let resource_substate: Resource = serde_wasm_bindgen::from_value(resource_substate_js)?;
-    let input_selection: ConfidentialTransferInputSelection = serde_wasm_bindgen::from_value(input_selection_js)?;
+    let destination_public_key =
+        RistrettoPublicKey::from_hex(destination_public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+
+    let resource_address = parse_resource_address_arg(resource_address)?;
+    let proof_from_resource = proof_from_resource
+        .map(|s| ResourceAddress::from_str(&s))
+        .transpose()
+        .map_err(|e| WalletLibError::invalid_argument("proof_from_resource", e))?;
+
+    let source_vault_id =
+        VaultId::from_str(source_vault_id).map_err(|e| WalletLibError::invalid_argument("source_vault_id", e))?;
+    let source_vault: Vault = from_js_value("source_vault", source_vault_js)?;
+    let resource_substate: Resource = from_js_value("resource_substate", resource_substate_js)?;
+    let input_selection: ConfidentialTransferInputSelection = from_js_value("input_selection", input_selection_js)?;
+    let coin_selection: Option<CoinSelectionStrategy> = from_js_value("coin_selection", coin_selection_js)?;
+    let output_key_indexes: Option<HashMap<String, String>> =
+        from_js_value("output_key_indexes", output_key_indexes_js)?;
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
 
     let params = ConfidentialTransferParams {
-        source_private_key,
+        source_private_key: source_private_key.clone(),
         source_public_key,
         source_account_address,
         destination_public_key,
@@ -224,22 +775,32 @@ pub fn create_confidential_transfer_transaction(
         source_vault,
         resource_substate,
         input_selection,
+        coin_selection: coin_selection.unwrap_or_default(),
+        output_key_indexes: parse_output_key_indexes(output_key_indexes.unwrap_or_default())?,
         source_vault_id,
+        epoch_window,
     };
 
-    Ok(build_confidential_transfer_transaction(params)?)
+    let transfer = build_confidential_transfer_transaction(params)?;
+    let encoded = EncodedTransaction::new(&transfer.transaction)?;
+    to_js_value(&EncodedConfidentialTransfer {
+        transaction: encoded.transaction,
+        change_output_key: transfer.change_output.as_ref().map(OutputKeyIndex::new),
+    })
 }
 
+
 #[wasm_bindgen]
 pub fn create_free_test_coins_transaction(
     is_new_account: bool,
-    account_private_key: &str,
+    account_private_key: &SecretKeyHandle,
     amount: i64,
     fee: i64,
-) -> Result<JsValue, JsError> {
-    let account_private_key = RistrettoSecretKey::from_hex(account_private_key)
-        .map_err(|e| JsError::new(&format!("Could not parse private key: {:?}", e)))?;
-    let account_public_key = RistrettoPublicKey::from_secret_key(&account_private_key);
+    epoch_window_js: JsValue,
+) -> Result<JsValue, WalletLibError> {
+    let account_private_key = account_private_key.secret_key();
+    let epoch_window = parse_epoch_window_arg(epoch_window_js)?;
+    let account_public_key = RistrettoPublicKey::from_secret_key(account_private_key);
     let account_component_address =
         get_account_address_from_public_key(&account_public_key.to_hex())?;
 
@@ -282,11 +843,10 @@ pub fn create_free_test_coins_transaction(
         args: args![Amount::new(fee)],
     });
 
-    let transaction = Transaction::builder()
+    let builder = Transaction::builder()
         .with_fee_instructions(instructions.to_vec())
-        .with_inputs(inputs)
-        .sign(&account_private_key)
-        .build();
+        .with_inputs(inputs);
+    let transaction = epoch_window.apply(builder).sign(account_private_key).build();
 
     encode_transaction(&transaction)
 }
@@ -294,13 +854,19 @@ pub fn create_free_test_coins_transaction(
 #[wasm_bindgen]
 pub fn get_confidential_balance(
     vault_js: JsValue,
-    account_private_key: &str,
-) -> Result<JsValue, JsError> {
-    let vault: Vault = serde_wasm_bindgen::from_value(vault_js)?;
-    let account_private_key = RistrettoSecretKey::from_hex(account_private_key)
-        .map_err(|e| JsError::new(&format!("Could not parse private key: {:?}", e)))?;
-    let balance = confidential_transfer::get_confidential_balance(&vault, &account_private_key)?;
-    Ok(serde_wasm_bindgen::to_value(&balance)?)
+    account_private_key: &SecretKeyHandle,
+) -> Result<JsValue, WalletLibError> {
+    let vault: Vault = from_js_value("vault", vault_js)?;
+    let balance = confidential_transfer::get_confidential_balance(&vault, account_private_key.secret_key())?;
+    to_js_value(&balance)
+}
+
+/// Builds a value lookup table with `size` baby steps, encoded as bytes.
+/// Building is the expensive part, so callers should persist the result and pass it to `view_vault_balance`.
+#[wasm_bindgen]
+pub fn build_value_lookup_table(size: Option<u64>) -> Result<Vec<u8>, WalletLibError> {
+    let table = BabyStepGiantStepTable::build(size.unwrap_or(DEFAULT_LOOKUP_TABLE_SIZE))?;
+    Ok(table.to_bytes())
 }
 
 #[wasm_bindgen]
@@ -308,41 +874,95 @@ pub fn view_vault_balance(
     vault_js: JsValue,
     minimum_expected_value: Option<u64>,
     maximum_expected_value: Option<u64>,
-    ecdsa_str: &str
-) -> Result<JsValue, JsError> {
+    secret_view_key: &SecretKeyHandle,
+    lookup_table: Option<Vec<u8>>,
+) -> Result<JsValue, WalletLibError> {
     // TODO: refactor to reuse the "get_confidential_balance" function
-    let vault: Vault = serde_wasm_bindgen::from_value(vault_js)?;
-    let secret_view_key = ecdsa_to_ristretto_private_key(ecdsa_str)?;
+    let vault: Vault = from_js_value("vault", vault_js)?;
+    let secret_view_key = secret_view_key.secret_key();
 
     #[allow(clippy::mutable_key_type)]
     let commitments = vault
         .get_confidential_commitments()
-        .ok_or_else(|| 
-            JsError::new(&format!("Vault dow not contain a confidential resource")))?;
+        .ok_or(WalletLibError::NotAConfidentialVault)?;
 
     let value_range = minimum_expected_value.unwrap_or(0)..=maximum_expected_value.unwrap_or(10_000_000_000);
 
-    let balances = ElgamalVerifiableBalance::batched_brute_force(
-        &secret_view_key,
-        value_range,
-        &mut AlwaysMissLookupTable,
-        commitments.values().filter_map(|output| output.viewable_balance.as_ref()),
-    )?;
+    let lookup_table = match lookup_table {
+        Some(bytes) => BabyStepGiantStepTable::from_bytes(&bytes)?,
+        None => BabyStepGiantStepTable::build(DEFAULT_LOOKUP_TABLE_SIZE)?,
+    };
 
     let result: HashMap<String, Option<u64>> = commitments
-            .keys()
-            .map(|c| c.as_public_key().to_string())
-            .zip(balances.clone())
+            .iter()
+            .map(|(c, output)| {
+                let balance = output
+                    .viewable_balance
+                    .as_ref()
+                    .and_then(|b| lookup_table.decrypt_balance(secret_view_key, b, value_range.clone()));
+                (c.as_public_key().to_string(), balance)
+            })
             .collect();
     
-    Ok(serde_wasm_bindgen::to_value(&result)?)        
+    to_js_value(&result)
+}
+
+#[derive(serde::Serialize)]
+struct EncodedTransaction {
+    transaction: String,
+}
+
+#[derive(serde::Serialize)]
+struct EncodedConfidentialTransfer {
+    transaction: String,
+    /// Key index of the change output sent back to the source account, to be stored by the caller
+    change_output_key: Option<OutputKeyIndex>,
+}
+
+#[derive(serde::Serialize)]
+struct EncodedAccountNftTransaction {
+    transaction: String,
+    component_address: String,
 }
 
 // serde-wasm has some limitations when the structs use the serde's "flatten" macro
 // See https://github.com/RReverser/serde-wasm-bindgen/issues/9 for more context on the problem
 // This causes the transaction being a empty JsValue if we encode it directly with serde_wasm_bindgen
 // So the simplest workaround is to return the transaction as JSON and then parsing it on the snap's TypeScript side
-fn encode_transaction(transaction: &Transaction) -> Result<JsValue, JsError> {
-    let json = serde_json::to_string(&transaction)?;
-    Ok(serde_wasm_bindgen::to_value(&json)?)
+pub(crate) fn encode_transaction(transaction: &Transaction) -> Result<JsValue, WalletLibError> {
+    to_js_value(&EncodedTransaction::new(transaction)?)
+}
+
+impl EncodedTransaction {
+    fn new(transaction: &Transaction) -> Result<Self, WalletLibError> {
+        let json = serde_json::to_string(&transaction).map_err(WalletLibError::encoding_failed)?;
+        Ok(Self { transaction: json })
+    }
+}
+
+fn parse_epoch_window_arg(epoch_window_js: JsValue) -> Result<EpochWindow, WalletLibError> {
+    let epoch_window: Option<EpochWindow> = from_js_value("epoch_window", epoch_window_js)?;
+    epoch_window.unwrap_or_default().validated()
+}
+
+// Transactions are passed as the JSON returned by the builders, instructions as plain JS values
+fn parse_instructions_arg(transaction_js: JsValue) -> Result<InstructionsInput, WalletLibError> {
+    match transaction_js.as_string() {
+        Some(json) => Ok(InstructionsInput::from_transaction(&parse_transaction_json(&json)?)),
+        None => from_js_value("transaction", transaction_js),
+    }
+}
+
+fn parse_resource_address_arg(resource_address: &str) -> Result<ResourceAddress, WalletLibError> {
+    ResourceAddress::from_str(resource_address).map_err(|e| WalletLibError::invalid_argument("resource_address", e))
+}
+
+pub(crate) fn parse_component_address_arg(component_address: &str) -> Result<ComponentAddress, WalletLibError> {
+    ComponentAddress::from_str(component_address)
+        .map_err(|e| WalletLibError::invalid_argument("component_address", e))
+}
+
+pub(crate) fn parse_template_address_arg(template_address: &str) -> Result<TemplateAddress, WalletLibError> {
+    TemplateAddress::from_hex(template_address)
+        .map_err(|e| WalletLibError::invalid_argument("template_address", format!("{:?}", e)))
 }
\ No newline at end of file
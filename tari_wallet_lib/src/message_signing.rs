@@ -0,0 +1,141 @@
+//! Schnorr signatures over arbitrary messages, such as login challenges or off-chain attestations.
+//! Every signature is bound to a domain (e.g. the website origin), so it cannot be replayed in another context.
+
+use std::fmt;
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use tari_crypto::hash_domain;
+use tari_crypto::hashing::{DomainSeparatedHash, DomainSeparatedHasher};
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::{from_hex, Hex};
+use tari_crypto::tari_utilities::ByteArray;
+
+hash_domain!(MessageSignatureDomain, "com.tari.snap.message_signature", 1);
+
+const LABEL_CHALLENGE: &str = "challenge";
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageSignatureError {
+    MissingDomain,
+    InvalidDomain(String),
+    InvalidSignature(String),
+    SigningFailed(String),
+}
+
+impl fmt::Display for MessageSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageSignatureError::MissingDomain => write!(f, "A domain is required to sign or verify messages"),
+            MessageSignatureError::InvalidDomain(reason) => write!(f, "Invalid domain: {}", reason),
+            MessageSignatureError::InvalidSignature(reason) => write!(f, "Invalid signature: {}", reason),
+            MessageSignatureError::SigningFailed(reason) => write!(f, "Could not sign message: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MessageSignatureError {}
+
+pub fn sign_message(
+    secret_key: &RistrettoSecretKey,
+    message: &[u8],
+    domain: &str,
+) -> Result<RistrettoSchnorr, MessageSignatureError> {
+    let nonce = RistrettoSecretKey::random(&mut rand::thread_rng());
+    sign_message_with_nonce(secret_key, nonce, message, domain)
+}
+
+/// Signs with a caller supplied nonce. Reusing a nonce leaks the secret key, so this is only used for test vectors.
+pub(crate) fn sign_message_with_nonce(
+    secret_key: &RistrettoSecretKey,
+    nonce: RistrettoSecretKey,
+    message: &[u8],
+    domain: &str,
+) -> Result<RistrettoSchnorr, MessageSignatureError> {
+    let public_key = RistrettoPublicKey::from_secret_key(secret_key);
+    let public_nonce = RistrettoPublicKey::from_secret_key(&nonce);
+    let challenge = message_challenge(&public_key, &public_nonce, message, domain)?;
+    RistrettoSchnorr::sign_raw_uniform(secret_key, nonce, challenge.as_ref())
+        .map_err(|e| MessageSignatureError::SigningFailed(format!("{:?}", e)))
+}
+
+pub fn verify_message_signature(
+    public_key: &RistrettoPublicKey,
+    message: &[u8],
+    signature: &RistrettoSchnorr,
+    domain: &str,
+) -> Result<bool, MessageSignatureError> {
+    let challenge = message_challenge(public_key, signature.get_public_nonce(), message, domain)?;
+    Ok(signature.verify_raw_uniform(public_key, challenge.as_ref()))
+}
+
+/// Encodes a signature as the hex of its public nonce followed by its scalar
+pub fn signature_to_hex(signature: &RistrettoSchnorr) -> String {
+    format!(
+        "{}{}",
+        signature.get_public_nonce().to_hex(),
+        signature.get_signature().to_hex()
+    )
+}
+
+pub fn signature_from_hex(signature_hex: &str) -> Result<RistrettoSchnorr, MessageSignatureError> {
+    let bytes = from_hex(signature_hex).map_err(|e| MessageSignatureError::InvalidSignature(format!("{:?}", e)))?;
+    if bytes.len() != 2 * KEY_LEN {
+        return Err(MessageSignatureError::InvalidSignature(format!(
+            "expected {} bytes but got {}",
+            2 * KEY_LEN,
+            bytes.len()
+        )));
+    }
+    let public_nonce = RistrettoPublicKey::from_canonical_bytes(&bytes[..KEY_LEN])
+        .map_err(|e| MessageSignatureError::InvalidSignature(format!("{:?}", e)))?;
+    let signature = RistrettoSecretKey::from_canonical_bytes(&bytes[KEY_LEN..])
+        .map_err(|e| MessageSignatureError::InvalidSignature(format!("{:?}", e)))?;
+    Ok(RistrettoSchnorr::new(public_nonce, signature))
+}
+
+fn message_challenge(
+    public_key: &RistrettoPublicKey,
+    public_nonce: &RistrettoPublicKey,
+    message: &[u8],
+    domain: &str,
+) -> Result<DomainSeparatedHash<Blake2b<U64>>, MessageSignatureError> {
+    if domain.trim().is_empty() {
+        return Err(MessageSignatureError::MissingDomain);
+    }
+    // the domain is hashed as given, so a padded domain would silently produce a different signature
+    if domain.trim() != domain {
+        return Err(MessageSignatureError::InvalidDomain(
+            "the domain cannot start or end with whitespace".to_string(),
+        ));
+    }
+    Ok(
+        DomainSeparatedHasher::<Blake2b<U64>, MessageSignatureDomain>::new_with_label(LABEL_CHALLENGE)
+            .chain(domain.as_bytes())
+            .chain(public_nonce.as_bytes())
+            .chain(public_key.as_bytes())
+            .chain(message)
+            .finalize(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET_KEY: &str = "bd7e71ef67228d750e165f44d4a0dbdaf2e5c27881dcc6714bdd6c5e3a67d402";
+    const NONCE: &str = "8569156e65b76e9c3dccf64d5b9dffa831f1436e5b03073915a680a15e3a9904";
+    const SIGNATURE: &str = "6e29fee6082e238475d40c6d4bed1d265ed5cd9d735a84a0380c09ff2d2e8d0d\
+                             bb08a21fa902b0b090d7cf51834d8525e7e6c22196f6a68d0263132203b61b0b";
+
+    #[test]
+    fn it_matches_the_known_vector() {
+        let secret_key = RistrettoSecretKey::from_hex(SECRET_KEY).unwrap();
+        let nonce = RistrettoSecretKey::from_hex(NONCE).unwrap();
+
+        let signature = sign_message_with_nonce(&secret_key, nonce, b"Sign in to example.com", "example.com").unwrap();
+        assert_eq!(signature_to_hex(&signature), SIGNATURE);
+    }
+}
@@ -1,20 +1,31 @@
 use serde::{Serialize, Deserialize};
 use tari_template_lib::prelude::Metadata;
-use wasm_bindgen::{JsValue, JsError};
+use wasm_bindgen::JsValue;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::error::{from_js_value, to_js_value, WalletLibError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MetadataField {
-    key: String,
-    value: String,
+    pub key: String,
+    pub value: String,
+}
+
+pub fn encode_metadata(metadata_js: JsValue) -> Result<JsValue, WalletLibError> {
+    let fields: Vec<MetadataField> = from_js_value("metadata", metadata_js)?;
+    let encoded_metadata = encode_metadata_fields(fields)?;
+    
+    to_js_value(&encoded_metadata)
 }
 
-pub fn encode_metadata(metadata_js: JsValue) -> Result<JsValue, JsError> {
-    let fields: Vec<MetadataField> = serde_wasm_bindgen::from_value(metadata_js)?;
+pub fn encode_metadata_fields(fields: Vec<MetadataField>) -> Result<Vec<u8>, WalletLibError> {
+    let metadata = metadata_from_fields(fields);
+    tari_bor::encode(&metadata).map_err(WalletLibError::encoding_failed)
+}
+
+pub fn metadata_from_fields(fields: Vec<MetadataField>) -> Metadata {
     let mut metadata = Metadata::new();
     for field in fields {
         metadata.insert(field.key, field.value);
     }
-    let encoded_metadata = tari_bor::encode(&metadata)?;
-    
-    Ok(serde_wasm_bindgen::to_value(&encoded_metadata)?)
-}
\ No newline at end of file
+    metadata
+}
@@ -0,0 +1,88 @@
+//! Transfers of non fungible tokens between accounts.
+
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::args;
+use tari_template_lib::prelude::{ComponentAddress, NonFungibleAddress, NonFungibleId, ResourceAddress};
+use tari_transaction::SubstateRequirement;
+
+use crate::component::account_address_from_public_key;
+use crate::error::WalletLibError;
+
+#[derive(Debug, Clone)]
+pub struct NftTransfer {
+    source_account_address: ComponentAddress,
+    destination_public_key: RistrettoPublicKey,
+    create_destination_account: bool,
+    resource_address: ResourceAddress,
+    ids: Vec<NonFungibleId>,
+}
+
+impl NftTransfer {
+    pub fn new(
+        source_account_address: ComponentAddress,
+        destination_public_key: RistrettoPublicKey,
+        create_destination_account: bool,
+        resource_address: ResourceAddress,
+        ids: Vec<NonFungibleId>,
+    ) -> Result<Self, WalletLibError> {
+        if ids.is_empty() {
+            return Err(WalletLibError::invalid_argument("ids", "there must be at least one id"));
+        }
+        for (i, id) in ids.iter().enumerate() {
+            if ids[..i].contains(id) {
+                return Err(WalletLibError::invalid_argument(
+                    &format!("ids[{}]", i),
+                    format!("the id {} is repeated", id.to_canonical_string()),
+                ));
+            }
+        }
+        Ok(Self {
+            source_account_address,
+            destination_public_key,
+            create_destination_account,
+            resource_address,
+            ids,
+        })
+    }
+
+    /// Withdraws each token into its own bucket and deposits it into the destination account
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let destination_account_address = account_address_from_public_key(&self.destination_public_key);
+        let mut instructions = vec![];
+        if self.create_destination_account {
+            instructions.push(Instruction::CreateAccount {
+                owner_public_key: self.destination_public_key.clone(),
+                workspace_bucket: None,
+            });
+        }
+        for (i, id) in self.ids.iter().enumerate() {
+            let bucket = format!("nft_{}", i);
+            instructions.push(Instruction::CallMethod {
+                component_address: self.source_account_address,
+                method: "withdraw_non_fungible".to_string(),
+                args: args![self.resource_address, id.clone()],
+            });
+            instructions.push(Instruction::PutLastInstructionOutputOnWorkspace {
+                key: bucket.clone().into_bytes(),
+            });
+            instructions.push(Instruction::CallMethod {
+                component_address: destination_account_address,
+                method: "deposit".to_string(),
+                args: args![Workspace(bucket)],
+            });
+        }
+        instructions
+    }
+
+    /// The resource and every token, which are not all referenced directly by the instructions
+    pub fn inputs(&self) -> Vec<SubstateRequirement> {
+        let mut inputs = vec![SubstateRequirement::new(SubstateId::Resource(self.resource_address), None)];
+        inputs.extend(self.ids.iter().map(|id| {
+            let address = NonFungibleAddress::new(self.resource_address, id.clone());
+            SubstateRequirement::new(SubstateId::NonFungible(address), None)
+        }));
+        inputs
+    }
+}
@@ -0,0 +1,64 @@
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroize;
+
+use crate::error::WalletLibError;
+use crate::key_derivation::{derive_ristretto_private_key, KeyDerivationVersion};
+use crate::key_manager::KeyManager;
+
+/// Secret key that never leaves the WASM memory.
+/// JavaScript only holds a pointer to it, and calling `free()` wipes the key.
+#[wasm_bindgen]
+pub struct SecretKeyHandle {
+    secret_key: RistrettoSecretKey,
+}
+
+impl SecretKeyHandle {
+    pub fn new(secret_key: RistrettoSecretKey) -> Self {
+        Self { secret_key }
+    }
+
+    pub fn secret_key(&self) -> &RistrettoSecretKey {
+        &self.secret_key
+    }
+}
+
+#[wasm_bindgen]
+impl SecretKeyHandle {
+    pub fn from_metamask_key(
+        ecdsa_str: &str,
+        version: Option<KeyDerivationVersion>,
+    ) -> Result<SecretKeyHandle, WalletLibError> {
+        let secret_key = derive_ristretto_private_key(ecdsa_str, version.unwrap_or_default())?;
+        Ok(Self::new(secret_key))
+    }
+
+    pub fn from_key_manager(
+        key_manager: &KeyManager,
+        branch: &str,
+        index: u64,
+    ) -> Result<SecretKeyHandle, WalletLibError> {
+        let secret_key = key_manager
+            .derive_key(branch, index)
+            .map_err(WalletLibError::key_derivation_failed)?;
+        Ok(Self::new(secret_key))
+    }
+
+    /// Imports a hex encoded key. Prefer the other constructors, as the hex string cannot be wiped from JavaScript.
+    pub fn from_hex(secret_key_hex: &str) -> Result<SecretKeyHandle, WalletLibError> {
+        let secret_key = RistrettoSecretKey::from_hex(secret_key_hex).map_err(WalletLibError::invalid_private_key)?;
+        Ok(Self::new(secret_key))
+    }
+
+    pub fn public_key(&self) -> String {
+        RistrettoPublicKey::from_secret_key(&self.secret_key).to_hex()
+    }
+}
+
+impl Drop for SecretKeyHandle {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
@@ -0,0 +1,319 @@
+//! "Sign-In with Tari" authentication messages.
+//! A dApp asks the wallet to sign a canonical message naming its domain and the user account, and its backend
+//! verifies the signature, the validity window and that the nonce was not used before.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use rand::RngCore;
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::{to_hex, Hex};
+
+use crate::component::get_account_address_from_public_key;
+use crate::message_signing::{self, MessageSignatureError};
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Tari account:";
+const PUBLIC_KEY_PREFIX: &str = "Public Key: ";
+const NONCE_PREFIX: &str = "Nonce: ";
+const ISSUED_AT_PREFIX: &str = "Issued At: ";
+const EXPIRATION_TIME_PREFIX: &str = "Expiration Time: ";
+const MIN_NONCE_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignInError {
+    InvalidMessage(String),
+    DomainMismatch { expected: String, found: String },
+    NotYetValid { issued_at: u64, now: u64 },
+    Expired { expiration_time: u64, now: u64 },
+    NonceReused(String),
+    AccountMismatch,
+    InvalidSignature,
+    Signature(MessageSignatureError),
+}
+
+impl fmt::Display for SignInError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignInError::InvalidMessage(reason) => write!(f, "Invalid sign-in message: {}", reason),
+            SignInError::DomainMismatch { expected, found } => {
+                write!(f, "Sign-in message is for domain '{}' but '{}' was expected", found, expected)
+            },
+            SignInError::NotYetValid { issued_at, now } => {
+                write!(f, "Sign-in message is issued at {}, which is after {}", issued_at, now)
+            },
+            SignInError::Expired { expiration_time, now } => {
+                write!(f, "Sign-in message expired at {}, it is now {}", expiration_time, now)
+            },
+            SignInError::NonceReused(nonce) => write!(f, "Sign-in nonce '{}' was already used", nonce),
+            SignInError::AccountMismatch => write!(f, "The account address does not belong to the public key"),
+            SignInError::InvalidSignature => write!(f, "Invalid sign-in signature"),
+            SignInError::Signature(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SignInError {}
+
+impl From<MessageSignatureError> for SignInError {
+    fn from(e: MessageSignatureError) -> Self {
+        SignInError::Signature(e)
+    }
+}
+
+/// Keeps track of the nonces that were already accepted, so a signed message cannot be replayed
+pub trait NonceStore {
+    /// Records the nonce, returning `false` if it was already recorded
+    fn insert_nonce(&mut self, nonce: &str) -> bool;
+}
+
+impl NonceStore for HashSet<String> {
+    fn insert_nonce(&mut self, nonce: &str) -> bool {
+        self.insert(nonce.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignInMessage {
+    pub domain: String,
+    pub account_address: String,
+    pub statement: Option<String>,
+    pub public_key: RistrettoPublicKey,
+    pub nonce: String,
+    /// Unix timestamp in seconds
+    pub issued_at: u64,
+    /// Unix timestamp in seconds
+    pub expiration_time: u64,
+}
+
+impl SignInMessage {
+    pub fn builder() -> SignInMessageBuilder {
+        SignInMessageBuilder::default()
+    }
+
+    pub fn sign(&self, secret_key: &RistrettoSecretKey) -> Result<RistrettoSchnorr, SignInError> {
+        if RistrettoPublicKey::from_secret_key(secret_key) != self.public_key {
+            return Err(SignInError::AccountMismatch);
+        }
+        Ok(message_signing::sign_message(secret_key, self.to_string().as_bytes(), &self.domain)?)
+    }
+
+    /// Checks the message against the expected domain and the current time, then verifies the signature and consumes
+    /// the nonce. The nonce is only recorded for messages that pass every other check.
+    pub fn verify<S: NonceStore>(
+        &self,
+        signature: &RistrettoSchnorr,
+        expected_domain: &str,
+        now: u64,
+        nonce_store: &mut S,
+    ) -> Result<(), SignInError> {
+        if self.domain != expected_domain {
+            return Err(SignInError::DomainMismatch {
+                expected: expected_domain.to_string(),
+                found: self.domain.clone(),
+            });
+        }
+        if now < self.issued_at {
+            return Err(SignInError::NotYetValid {
+                issued_at: self.issued_at,
+                now,
+            });
+        }
+        if now >= self.expiration_time {
+            return Err(SignInError::Expired {
+                expiration_time: self.expiration_time,
+                now,
+            });
+        }
+        if account_address_of(&self.public_key)? != self.account_address {
+            return Err(SignInError::AccountMismatch);
+        }
+
+        let is_valid = message_signing::verify_message_signature(
+            &self.public_key,
+            self.to_string().as_bytes(),
+            signature,
+            &self.domain,
+        )?;
+        if !is_valid {
+            return Err(SignInError::InvalidSignature);
+        }
+
+        if !nonce_store.insert_nonce(&self.nonce) {
+            return Err(SignInError::NonceReused(self.nonce.clone()));
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), SignInError> {
+        if self.domain.is_empty() || self.domain.chars().any(char::is_whitespace) {
+            return Err(SignInError::InvalidMessage("the domain must be a non empty host".to_string()));
+        }
+        if let Some(statement) = &self.statement {
+            if statement.is_empty() || statement.contains('\n') {
+                return Err(SignInError::InvalidMessage(
+                    "the statement must be a single non empty line".to_string(),
+                ));
+            }
+        }
+        if self.nonce.len() < MIN_NONCE_LEN || !self.nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SignInError::InvalidMessage(format!(
+                "the nonce must have at least {} alphanumeric characters",
+                MIN_NONCE_LEN
+            )));
+        }
+        if self.expiration_time <= self.issued_at {
+            return Err(SignInError::InvalidMessage(
+                "the expiration time must be after the issue time".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Canonical serialization, which is exactly what gets signed
+impl fmt::Display for SignInMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, HEADER_SUFFIX)?;
+        writeln!(f, "{}", self.account_address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "{}{}", PUBLIC_KEY_PREFIX, self.public_key.to_hex())?;
+        writeln!(f, "{}{}", NONCE_PREFIX, self.nonce)?;
+        writeln!(f, "{}{}", ISSUED_AT_PREFIX, self.issued_at)?;
+        write!(f, "{}{}", EXPIRATION_TIME_PREFIX, self.expiration_time)
+    }
+}
+
+impl FromStr for SignInMessage {
+    type Err = SignInError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| SignInError::InvalidMessage(reason.to_string());
+        let lines: Vec<&str> = s.split('\n').collect();
+        let statement = match lines.len() {
+            7 => None,
+            9 => Some(lines[3].to_string()),
+            _ => return Err(invalid("unexpected number of lines")),
+        };
+        let fields = &lines[lines.len() - 4..];
+
+        let domain = lines[0]
+            .strip_suffix(HEADER_SUFFIX)
+            .ok_or_else(|| invalid("missing header"))?;
+        let public_key = field(fields[0], PUBLIC_KEY_PREFIX)?;
+        let public_key = RistrettoPublicKey::from_hex(public_key).map_err(|_| invalid("invalid public key"))?;
+        let issued_at = field(fields[2], ISSUED_AT_PREFIX)?
+            .parse()
+            .map_err(|_| invalid("invalid issue time"))?;
+        let expiration_time = field(fields[3], EXPIRATION_TIME_PREFIX)?
+            .parse()
+            .map_err(|_| invalid("invalid expiration time"))?;
+
+        let message = SignInMessage {
+            domain: domain.to_string(),
+            account_address: lines[1].to_string(),
+            statement,
+            public_key,
+            nonce: field(fields[1], NONCE_PREFIX)?.to_string(),
+            issued_at,
+            expiration_time,
+        };
+        message.validate()?;
+
+        // Anything that does not serialize back to the same text (extra spaces, leading zeros...) is rejected,
+        // so there is exactly one valid encoding for each message
+        if message.to_string() != s {
+            return Err(invalid("the message is not in canonical form"));
+        }
+
+        Ok(message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SignInMessageBuilder {
+    domain: Option<String>,
+    statement: Option<String>,
+    public_key: Option<RistrettoPublicKey>,
+    nonce: Option<String>,
+    issued_at: Option<u64>,
+    expiration_time: Option<u64>,
+}
+
+impl SignInMessageBuilder {
+    pub fn with_domain<T: Into<String>>(mut self, domain: T) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn with_statement<T: Into<String>>(mut self, statement: T) -> Self {
+        self.statement = Some(statement.into());
+        self
+    }
+
+    pub fn with_public_key(mut self, public_key: RistrettoPublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    pub fn with_secret_key(self, secret_key: &RistrettoSecretKey) -> Self {
+        self.with_public_key(RistrettoPublicKey::from_secret_key(secret_key))
+    }
+
+    pub fn with_nonce<T: Into<String>>(mut self, nonce: T) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    pub fn with_issued_at(mut self, issued_at: u64) -> Self {
+        self.issued_at = Some(issued_at);
+        self
+    }
+
+    pub fn with_expiration_time(mut self, expiration_time: u64) -> Self {
+        self.expiration_time = Some(expiration_time);
+        self
+    }
+
+    pub fn build(self) -> Result<SignInMessage, SignInError> {
+        let missing = |field: &str| SignInError::InvalidMessage(format!("missing {}", field));
+        let public_key = self.public_key.ok_or_else(|| missing("public key"))?;
+
+        let message = SignInMessage {
+            domain: self.domain.ok_or_else(|| missing("domain"))?,
+            account_address: account_address_of(&public_key)?,
+            statement: self.statement,
+            public_key,
+            nonce: self.nonce.ok_or_else(|| missing("nonce"))?,
+            issued_at: self.issued_at.ok_or_else(|| missing("issue time"))?,
+            expiration_time: self.expiration_time.ok_or_else(|| missing("expiration time"))?,
+        };
+        message.validate()?;
+
+        Ok(message)
+    }
+}
+
+/// Random nonce for backends to hand out with each sign-in request
+pub fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+fn field<'a>(line: &'a str, prefix: &str) -> Result<&'a str, SignInError> {
+    line.strip_prefix(prefix)
+        .ok_or_else(|| SignInError::InvalidMessage(format!("missing field '{}'", prefix.trim_end_matches(": "))))
+}
+
+fn account_address_of(public_key: &RistrettoPublicKey) -> Result<String, SignInError> {
+    let account_address =
+        get_account_address_from_public_key(&public_key.to_hex()).map_err(|_| SignInError::AccountMismatch)?;
+    Ok(account_address.to_string())
+}
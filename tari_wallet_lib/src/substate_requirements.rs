@@ -0,0 +1,90 @@
+//! Inference of the substates a transaction needs as inputs, from its instructions.
+//! Callers only need to add the substates that are not referenced directly, e.g. a non fungible withdrawn by id.
+
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::args::Arg;
+use tari_template_lib::constants::PUBLIC_IDENTITY_RESOURCE_ADDRESS;
+use tari_template_lib::models::VaultId;
+use tari_template_lib::prelude::{ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_transaction::SubstateRequirement;
+
+use crate::component::account_address_from_public_key;
+
+/// Returns the substates used by the instructions, without duplicates and in the order they are first used.
+/// This covers the components that are called and the resources, badges and vaults passed as top level arguments.
+/// Substates created by the transaction are not included, as they do not exist yet: the accounts of `CreateAccount`
+/// instructions and the `created_components`, which are the components the caller knows a `CallFunction` creates.
+pub fn infer_substate_requirements(
+    fee_instructions: &[Instruction],
+    instructions: &[Instruction],
+    created_components: &[ComponentAddress],
+) -> Vec<SubstateRequirement> {
+    let created: Vec<SubstateId> = fee_instructions
+        .iter()
+        .chain(instructions)
+        .filter_map(|instruction| match instruction {
+            Instruction::CreateAccount { owner_public_key, .. } => {
+                Some(SubstateId::Component(account_address_from_public_key(owner_public_key)))
+            },
+            _ => None,
+        })
+        .chain(created_components.iter().map(|address| SubstateId::Component(*address)))
+        .collect();
+
+    let mut substate_ids: Vec<SubstateId> = vec![];
+    for instruction in fee_instructions.iter().chain(instructions) {
+        for substate_id in referenced_substates(instruction) {
+            if !created.contains(&substate_id) && !substate_ids.contains(&substate_id) {
+                substate_ids.push(substate_id);
+            }
+        }
+    }
+
+    substate_ids
+        .into_iter()
+        .map(|substate_id| SubstateRequirement::new(substate_id, None))
+        .collect()
+}
+
+fn referenced_substates(instruction: &Instruction) -> Vec<SubstateId> {
+    match instruction {
+        Instruction::CallMethod {
+            component_address,
+            args,
+            ..
+        } => {
+            let mut substate_ids = vec![SubstateId::Component(*component_address)];
+            substate_ids.extend(args.iter().flat_map(arg_substates));
+            substate_ids
+        },
+        Instruction::CallFunction { args, .. } => args.iter().flat_map(arg_substates).collect(),
+        _ => vec![],
+    }
+}
+
+// Addresses are encoded with their own CBOR tags, so each type only decodes from its own encoding
+fn arg_substates(arg: &Arg) -> Vec<SubstateId> {
+    let bytes = match arg {
+        Arg::Literal(bytes) => bytes,
+        Arg::Workspace(_) => return vec![],
+    };
+    if let Ok(address) = tari_bor::decode::<ComponentAddress>(bytes) {
+        return vec![SubstateId::Component(address)];
+    }
+    if let Ok(address) = tari_bor::decode::<ResourceAddress>(bytes) {
+        return vec![SubstateId::Resource(address)];
+    }
+    if let Ok(address) = tari_bor::decode::<VaultId>(bytes) {
+        return vec![SubstateId::Vault(address)];
+    }
+    if let Ok(badge) = tari_bor::decode::<NonFungibleAddress>(bytes) {
+        let resource = SubstateId::Resource(*badge.resource_address());
+        // Public key badges are virtual, there is no substate for them
+        if *badge.resource_address() == PUBLIC_IDENTITY_RESOURCE_ADDRESS {
+            return vec![resource];
+        }
+        return vec![resource, SubstateId::NonFungible(badge)];
+    }
+    vec![]
+}
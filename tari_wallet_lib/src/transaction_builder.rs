@@ -0,0 +1,260 @@
+//! Stateful transaction builder for JavaScript.
+//! Instructions are validated as they are added, so a mistake is reported at the call that made it instead of when
+//! the network rejects the transaction.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::prelude::Amount;
+use tari_transaction::{SubstateRequirement, Transaction};
+use wasm_bindgen::prelude::*;
+
+use crate::{encode_transaction, parse_component_address_arg, parse_template_address_arg};
+use crate::epoch_window::EpochWindow;
+use crate::error::{from_js_value, WalletLibError};
+use crate::secret_key::SecretKeyHandle;
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct TransactionBuilder {
+    fee_instructions: Vec<Instruction>,
+    instructions: Vec<Instruction>,
+    inputs: Vec<SubstateRequirement>,
+    workspace_keys: HashSet<String>,
+    /// The fee instructions are checked on their own, as they run before the other instructions
+    fee_workspace_keys: HashSet<String>,
+    epoch_window: EpochWindow,
+    /// Set by the first signature. The signatures cover the whole transaction, so it cannot change afterwards.
+    signed: Option<tari_transaction::TransactionBuilder>,
+}
+
+impl TransactionBuilder {
+    pub fn add_instruction(&mut self, instruction: Instruction) -> Result<(), WalletLibError> {
+        self.check_not_signed()?;
+        check_instruction(&instruction, &self.instructions, &mut self.workspace_keys)?;
+        self.instructions.push(instruction);
+        Ok(())
+    }
+
+    pub fn add_fee_instruction(&mut self, instruction: Instruction) -> Result<(), WalletLibError> {
+        self.check_not_signed()?;
+        check_instruction(&instruction, &self.fee_instructions, &mut self.fee_workspace_keys)?;
+        self.fee_instructions.push(instruction);
+        Ok(())
+    }
+
+    pub fn add_input_requirement(&mut self, input: SubstateRequirement) -> Result<(), WalletLibError> {
+        self.check_not_signed()?;
+        if self.inputs.iter().any(|i| i.substate_id() == input.substate_id()) {
+            return Err(WalletLibError::invalid_transaction(format!(
+                "the input {} was already added",
+                input.substate_id()
+            )));
+        }
+        self.inputs.push(input);
+        Ok(())
+    }
+
+    pub fn set_epoch_window(&mut self, epoch_window: EpochWindow) -> Result<(), WalletLibError> {
+        self.check_not_signed()?;
+        self.epoch_window = epoch_window.validated()?;
+        Ok(())
+    }
+
+    pub fn sign_with(&mut self, secret_key: &RistrettoSecretKey) -> Result<(), WalletLibError> {
+        let builder = match self.signed.take() {
+            Some(builder) => builder,
+            None => {
+                if self.fee_instructions.is_empty() {
+                    return Err(WalletLibError::invalid_transaction("the transaction does not pay any fees"));
+                }
+                let builder = Transaction::builder()
+                    .with_fee_instructions(self.fee_instructions.clone())
+                    .with_instructions(self.instructions.clone())
+                    .with_inputs(self.inputs.clone());
+                self.epoch_window.apply(builder)
+            },
+        };
+        self.signed = Some(builder.sign(secret_key));
+        Ok(())
+    }
+
+    pub fn build_transaction(self) -> Result<Transaction, WalletLibError> {
+        self.signed
+            .map(|builder| builder.build())
+            .ok_or_else(|| WalletLibError::invalid_transaction("the transaction must be signed before building it"))
+    }
+
+    fn check_not_signed(&self) -> Result<(), WalletLibError> {
+        if self.signed.is_some() {
+            return Err(WalletLibError::invalid_transaction(
+                "the transaction is already signed and cannot be modified",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl TransactionBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TransactionBuilder {
+        Self::default()
+    }
+
+    pub fn call_method(
+        &mut self,
+        component_address: &str,
+        method: &str,
+        args_js: JsValue,
+    ) -> Result<(), WalletLibError> {
+        let args: Vec<Arg> = from_js_value("args", args_js)?;
+        self.add_instruction(Instruction::CallMethod {
+            component_address: parse_component_address_arg(component_address)?,
+            method: method.to_string(),
+            args,
+        })
+    }
+
+    pub fn call_function(
+        &mut self,
+        template_address: &str,
+        function: &str,
+        args_js: JsValue,
+    ) -> Result<(), WalletLibError> {
+        let template_address = parse_template_address_arg(template_address)?;
+        let args: Vec<Arg> = from_js_value("args", args_js)?;
+        self.add_instruction(Instruction::CallFunction {
+            template_address,
+            function: function.to_string(),
+            args,
+        })
+    }
+
+    pub fn put_last_output_on_workspace(&mut self, name: &str) -> Result<(), WalletLibError> {
+        self.add_instruction(Instruction::PutLastInstructionOutputOnWorkspace {
+            key: name.as_bytes().to_vec(),
+        })
+    }
+
+    /// Creates the account of `owner_public_key_hex`, optionally depositing a bucket from the workspace into it
+    pub fn create_account(
+        &mut self,
+        owner_public_key_hex: &str,
+        workspace_bucket: Option<String>,
+    ) -> Result<(), WalletLibError> {
+        let owner_public_key =
+            RistrettoPublicKey::from_hex(owner_public_key_hex).map_err(WalletLibError::invalid_public_key)?;
+        self.add_instruction(Instruction::CreateAccount {
+            owner_public_key,
+            workspace_bucket,
+        })
+    }
+
+    pub fn pay_fee(&mut self, account_address: &str, fee: i64) -> Result<(), WalletLibError> {
+        if fee <= 0 {
+            return Err(WalletLibError::invalid_argument("fee", "the fee must be positive"));
+        }
+        self.add_fee_instruction(Instruction::CallMethod {
+            component_address: parse_component_address_arg(account_address)?,
+            method: "pay_fee".to_string(),
+            args: args![Amount::new(fee)],
+        })
+    }
+
+    pub fn add_input(&mut self, substate_id: &str, version: Option<u32>) -> Result<(), WalletLibError> {
+        let substate_id =
+            SubstateId::from_str(substate_id).map_err(|e| WalletLibError::invalid_argument("substate_id", e))?;
+        self.add_input_requirement(SubstateRequirement::new(substate_id, version))
+    }
+
+    /// Limits the epochs in which the network accepts the transaction. Either bound can be left open.
+    pub fn set_epoch_bounds(&mut self, min_epoch: Option<u64>, max_epoch: Option<u64>) -> Result<(), WalletLibError> {
+        self.set_epoch_window(EpochWindow::new(min_epoch, max_epoch)?)
+    }
+
+    /// Signs the transaction. It can be signed by several keys, but it cannot be modified after the first signature.
+    pub fn sign(&mut self, secret_key: &SecretKeyHandle) -> Result<(), WalletLibError> {
+        self.sign_with(secret_key.secret_key())
+    }
+
+    /// Returns the signed transaction encoded as JSON
+    pub fn build(self) -> Result<JsValue, WalletLibError> {
+        encode_transaction(&self.build_transaction()?)
+    }
+}
+
+/// Checks `instruction` against the `previous` instructions of the same list, which defined `workspace_keys`
+fn check_instruction(
+    instruction: &Instruction,
+    previous: &[Instruction],
+    workspace_keys: &mut HashSet<String>,
+) -> Result<(), WalletLibError> {
+    match instruction {
+        Instruction::CallMethod { method, args, .. } => {
+            check_name("method", method)?;
+            check_workspace_args(workspace_keys, args)?;
+        },
+        Instruction::CallFunction { function, args, .. } => {
+            check_name("function", function)?;
+            check_workspace_args(workspace_keys, args)?;
+        },
+        Instruction::PutLastInstructionOutputOnWorkspace { key } => {
+            if previous.is_empty() {
+                return Err(WalletLibError::invalid_transaction(
+                    "there is no previous instruction to take the output from",
+                ));
+            }
+            let key = String::from_utf8(key.clone())
+                .map_err(|_| WalletLibError::invalid_argument("workspace key", "must be valid UTF-8"))?;
+            check_name("workspace key", &key)?;
+            if !workspace_keys.insert(key.clone()) {
+                return Err(WalletLibError::invalid_transaction(format!(
+                    "the workspace key '{}' is already in use",
+                    key
+                )));
+            }
+        },
+        Instruction::CreateAccount {
+            workspace_bucket: Some(bucket),
+            ..
+        } => check_workspace_key(workspace_keys, bucket.as_bytes())?,
+        _ => {},
+    }
+    Ok(())
+}
+
+fn check_workspace_args(workspace_keys: &HashSet<String>, args: &[Arg]) -> Result<(), WalletLibError> {
+    for arg in args {
+        if let Arg::Workspace(key) = arg {
+            check_workspace_key(workspace_keys, key)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_workspace_key(workspace_keys: &HashSet<String>, key: &[u8]) -> Result<(), WalletLibError> {
+    let key = String::from_utf8_lossy(key);
+    // Keys can index into a workspace value (e.g. "bucket.0"), only the root has to be defined
+    let root = key.split('.').next().unwrap_or_default();
+    if !workspace_keys.contains(root) {
+        return Err(WalletLibError::invalid_transaction(format!(
+            "the workspace key '{}' is not defined by any previous instruction",
+            key
+        )));
+    }
+    Ok(())
+}
+
+fn check_name(argument: &str, name: &str) -> Result<(), WalletLibError> {
+    if name.trim().is_empty() {
+        return Err(WalletLibError::invalid_argument(argument, "cannot be empty"));
+    }
+    Ok(())
+}
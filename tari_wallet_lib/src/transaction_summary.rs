@@ -0,0 +1,335 @@
+//! Human readable summary of a transaction, shown to users before they sign it.
+
+use serde::{Deserialize, Serialize};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::{to_hex, Hex};
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args::Arg;
+use tari_template_lib::models::VaultId;
+use tari_template_lib::prelude::{ComponentAddress, NonFungibleAddress, NonFungibleId, ResourceAddress};
+use tari_transaction::Transaction;
+
+use crate::component::account_address_from_public_key;
+use crate::epoch_window::EpochWindow;
+use crate::error::WalletLibError;
+
+/// Instruction argument decoded back from its CBOR encoding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum DecodedArg {
+    Amount(i64),
+    /// Integer literal that is not known to be an amount
+    Integer(i64),
+    Address(String),
+    NonFungibleId(String),
+    String(String),
+    Bool(bool),
+    Workspace(String),
+    /// Hex of a literal that is none of the known types
+    Literal(String),
+}
+
+impl DecodedArg {
+    pub fn decode(arg: &Arg) -> Self {
+        match arg {
+            Arg::Workspace(key) => DecodedArg::Workspace(String::from_utf8_lossy(key).into_owned()),
+            Arg::Literal(bytes) => decode_literal(bytes),
+        }
+    }
+}
+
+// Addresses are encoded with their own CBOR tags, so each type only decodes from its own encoding
+fn decode_literal(bytes: &[u8]) -> DecodedArg {
+    if let Ok(address) = tari_bor::decode::<ResourceAddress>(bytes) {
+        return DecodedArg::Address(address.to_string());
+    }
+    if let Ok(address) = tari_bor::decode::<ComponentAddress>(bytes) {
+        return DecodedArg::Address(address.to_string());
+    }
+    if let Ok(address) = tari_bor::decode::<VaultId>(bytes) {
+        return DecodedArg::Address(address.to_string());
+    }
+    if let Ok(address) = tari_bor::decode::<NonFungibleAddress>(bytes) {
+        return DecodedArg::Address(address.to_string());
+    }
+    if let Ok(id) = tari_bor::decode::<NonFungibleId>(bytes) {
+        return DecodedArg::NonFungibleId(id.to_canonical_string());
+    }
+    // amounts are encoded as plain integers, see `label_amounts`
+    if let Ok(v) = tari_bor::decode::<i64>(bytes) {
+        return DecodedArg::Integer(v);
+    }
+    if let Ok(s) = tari_bor::decode::<String>(bytes) {
+        return DecodedArg::String(s);
+    }
+    if let Ok(b) = tari_bor::decode::<bool>(bytes) {
+        return DecodedArg::Bool(b);
+    }
+    DecodedArg::Literal(to_hex(bytes))
+}
+
+impl std::fmt::Display for DecodedArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodedArg::Amount(v) | DecodedArg::Integer(v) => write!(f, "{}", v),
+            DecodedArg::Address(v) | DecodedArg::NonFungibleId(v) | DecodedArg::String(v) => write!(f, "{}", v),
+            DecodedArg::Bool(v) => write!(f, "{}", v),
+            DecodedArg::Workspace(v) => write!(f, "{}", v),
+            DecodedArg::Literal(v) => write!(f, "0x{}", v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionStep {
+    pub description: String,
+    /// Whether the step withdraws from, deposits into or otherwise uses the account of the signer
+    pub touches_own_account: bool,
+    pub is_fee_instruction: bool,
+    pub instruction: String,
+    pub target: Option<String>,
+    pub method: Option<String>,
+    pub args: Vec<DecodedArg>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    pub signer_account: Option<String>,
+    pub steps: Vec<TransactionStep>,
+    /// First epoch in which the network accepts the transaction
+    pub min_epoch: Option<u64>,
+    /// Last epoch in which the network accepts the transaction
+    pub max_epoch: Option<u64>,
+}
+
+/// Instructions to summarize, either a list of instructions or both lists of a transaction
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum InstructionsInput {
+    Transaction {
+        #[serde(default)]
+        fee_instructions: Vec<Instruction>,
+        instructions: Vec<Instruction>,
+        #[serde(default)]
+        min_epoch: Option<u64>,
+        #[serde(default)]
+        max_epoch: Option<u64>,
+    },
+    Instructions(Vec<Instruction>),
+}
+
+pub fn summarize_transaction(
+    transaction: &Transaction,
+    signer_public_key: Option<&RistrettoPublicKey>,
+) -> TransactionSummary {
+    let summary = summarize_instructions(transaction.fee_instructions(), transaction.instructions(), signer_public_key);
+    summary.with_epoch_window(EpochWindow::of_transaction(transaction))
+}
+
+pub fn summarize_instructions(
+    fee_instructions: &[Instruction],
+    instructions: &[Instruction],
+    signer_public_key: Option<&RistrettoPublicKey>,
+) -> TransactionSummary {
+    let signer = signer_public_key.map(|pk| Signer {
+        public_key: pk.clone(),
+        account_address: account_address_from_public_key(pk),
+    });
+
+    let fee_steps = fee_instructions.iter().map(|i| summarize_instruction(i, true, signer.as_ref()));
+    let steps = instructions.iter().map(|i| summarize_instruction(i, false, signer.as_ref()));
+
+    TransactionSummary {
+        signer_account: signer.as_ref().map(|s| s.account_address.to_string()),
+        steps: fee_steps.chain(steps).collect(),
+        min_epoch: None,
+        max_epoch: None,
+    }
+}
+
+impl TransactionSummary {
+    pub fn with_epoch_window(mut self, epoch_window: EpochWindow) -> Self {
+        self.min_epoch = epoch_window.min_epoch;
+        self.max_epoch = epoch_window.max_epoch;
+        self
+    }
+}
+
+impl InstructionsInput {
+    pub fn from_transaction(transaction: &Transaction) -> Self {
+        let epoch_window = EpochWindow::of_transaction(transaction);
+        InstructionsInput::Transaction {
+            fee_instructions: transaction.fee_instructions().to_vec(),
+            instructions: transaction.instructions().to_vec(),
+            min_epoch: epoch_window.min_epoch,
+            max_epoch: epoch_window.max_epoch,
+        }
+    }
+
+    pub fn fee_instructions(&self) -> &[Instruction] {
+        match self {
+            InstructionsInput::Transaction { fee_instructions, .. } => fee_instructions,
+            InstructionsInput::Instructions(_) => &[],
+        }
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        match self {
+            InstructionsInput::Transaction { instructions, .. } | InstructionsInput::Instructions(instructions) => {
+                instructions
+            },
+        }
+    }
+
+    pub fn epoch_window(&self) -> EpochWindow {
+        match self {
+            InstructionsInput::Transaction { min_epoch, max_epoch, .. } => EpochWindow {
+                min_epoch: *min_epoch,
+                max_epoch: *max_epoch,
+            },
+            InstructionsInput::Instructions(_) => EpochWindow::default(),
+        }
+    }
+
+    pub fn summarize(&self, signer_public_key: Option<&RistrettoPublicKey>) -> TransactionSummary {
+        summarize_instructions(self.fee_instructions(), self.instructions(), signer_public_key)
+            .with_epoch_window(self.epoch_window())
+    }
+}
+
+pub fn parse_transaction_json(json: &str) -> Result<Transaction, WalletLibError> {
+    serde_json::from_str(json).map_err(|e| WalletLibError::invalid_argument("transaction", e))
+}
+
+struct Signer {
+    public_key: RistrettoPublicKey,
+    account_address: ComponentAddress,
+}
+
+fn summarize_instruction(
+    instruction: &Instruction,
+    is_fee_instruction: bool,
+    signer: Option<&Signer>,
+) -> TransactionStep {
+    let mut step = TransactionStep {
+        description: String::new(),
+        touches_own_account: false,
+        is_fee_instruction,
+        instruction: instruction_name(instruction),
+        target: None,
+        method: None,
+        args: vec![],
+    };
+
+    match instruction {
+        Instruction::CallMethod {
+            component_address,
+            method,
+            args,
+        } => {
+            let is_own = signer.map_or(false, |s| s.account_address == *component_address);
+            let mut args: Vec<DecodedArg> = args.iter().map(DecodedArg::decode).collect();
+            label_amounts(method, &mut args);
+            let account = if is_own {
+                "your account".to_string()
+            } else {
+                component_address.to_string()
+            };
+            step.description = describe_method_call(method, &args, &account);
+            step.touches_own_account = is_own;
+            step.target = Some(component_address.to_string());
+            step.method = Some(method.clone());
+            step.args = args;
+        },
+        Instruction::CallFunction {
+            template_address,
+            function,
+            args,
+        } => {
+            let args: Vec<DecodedArg> = args.iter().map(DecodedArg::decode).collect();
+            step.description = format!(
+                "Call function {} of template {}{}",
+                function,
+                template_address,
+                describe_args(&args)
+            );
+            step.target = Some(template_address.to_string());
+            step.method = Some(function.clone());
+            step.args = args;
+        },
+        Instruction::CreateAccount {
+            owner_public_key,
+            workspace_bucket,
+        } => {
+            let is_own = signer.map_or(false, |s| s.public_key == *owner_public_key);
+            let owner = if is_own {
+                "your account".to_string()
+            } else {
+                format!("an account for {}", owner_public_key.to_hex())
+            };
+            step.description = match workspace_bucket {
+                Some(bucket) => format!("Create {} and deposit {} into it", owner, bucket),
+                None => format!("Create {}", owner),
+            };
+            step.touches_own_account = is_own;
+            step.target = Some(account_address_from_public_key(owner_public_key).to_string());
+        },
+        Instruction::PutLastInstructionOutputOnWorkspace { key } => {
+            step.description = format!("Keep the result of the previous step as {}", String::from_utf8_lossy(key));
+        },
+        _ => {
+            step.description = format!("Run instruction {}", step.instruction);
+        },
+    }
+
+    step
+}
+
+// An integer argument is only labelled an amount for the account methods that take one
+fn label_amounts(method: &str, args: &mut [DecodedArg]) {
+    let amount_index = match method {
+        "withdraw" => 1,
+        "pay_fee" => 0,
+        _ => return,
+    };
+    if let Some(arg) = args.get_mut(amount_index) {
+        if let DecodedArg::Integer(v) = *arg {
+            *arg = DecodedArg::Amount(v);
+        }
+    }
+}
+
+fn describe_method_call(method: &str, args: &[DecodedArg], account: &str) -> String {
+    match (method, args) {
+        ("withdraw", [resource, amount]) => format!("Withdraw {} of {} from {}", amount, resource, account),
+        ("withdraw_non_fungible", [resource, id]) => {
+            format!("Withdraw the NFT {} of {} from {}", id, resource, account)
+        },
+        ("withdraw_confidential", [resource, ..]) => {
+            format!("Withdraw confidential funds of {} from {}", resource, account)
+        },
+        ("deposit", [bucket]) => format!("Deposit {} into {}", bucket, account),
+        ("pay_fee", [amount]) => format!("Pay a fee of up to {} from {}", amount, account),
+        ("create_proof_for_resource", [resource]) => {
+            format!("Create a proof of ownership of {} from {}", resource, account)
+        },
+        _ => format!("Call method {} on {}{}", method, account, describe_args(args)),
+    }
+}
+
+fn describe_args(args: &[DecodedArg]) -> String {
+    if args.is_empty() {
+        return String::new();
+    }
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    format!(" with {}", args.join(", "))
+}
+
+// Name of the instruction variant, taken from its externally tagged serialization
+fn instruction_name(instruction: &Instruction) -> String {
+    match serde_json::to_value(instruction) {
+        Ok(serde_json::Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        Ok(serde_json::Value::String(name)) => name,
+        _ => "Unknown".to_string(),
+    }
+}
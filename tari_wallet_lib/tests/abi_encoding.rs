@@ -0,0 +1,167 @@
+mod common;
+
+use serde_json::json;
+use tari_engine_types::instruction::Instruction;
+use tari_template_abi::TemplateDef;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{Amount, ComponentAddress};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::abi_encoding::{build_call_function_instruction, build_call_method_instruction};
+use tari_wallet_lib::error::WalletLibError;
+
+use common::{public_key, PUBLIC_KEY};
+
+fn template_def() -> TemplateDef {
+    serde_json::from_value(json!({
+        "V1": {
+            "template_name": "Counter",
+            "tari_version": "0.1.0",
+            "functions": [
+                {
+                    "name": "new",
+                    "arguments": [
+                        { "name": "name", "arg_type": "String" },
+                        { "name": "limits", "arg_type": { "Tuple": ["U32", { "Other": { "name": "Option<u64>" } }] } }
+                    ],
+                    "output": { "Other": { "name": "Component<Counter>" } },
+                    "is_mut": false
+                },
+                {
+                    "name": "deposit",
+                    "arguments": [
+                        { "name": "self", "arg_type": { "Other": { "name": "&mut self" } } },
+                        { "name": "bucket", "arg_type": { "Other": { "name": "Bucket" } } },
+                        { "name": "fee", "arg_type": { "Other": { "name": "Amount" } } }
+                    ],
+                    "output": "Unit",
+                    "is_mut": true
+                },
+                {
+                    "name": "set_metadata",
+                    "arguments": [
+                        { "name": "self", "arg_type": { "Other": { "name": "&mut self" } } },
+                        { "name": "metadata", "arg_type": { "Other": { "name": "Metadata" } } }
+                    ],
+                    "output": "Unit",
+                    "is_mut": true
+                }
+            ]
+        }
+    }))
+    .unwrap()
+}
+
+fn account_address() -> ComponentAddress {
+    account_address_from_public_key(&public_key(PUBLIC_KEY))
+}
+
+fn invalid_argument<T: std::fmt::Debug>(result: Result<T, WalletLibError>) -> (String, String) {
+    match result {
+        Err(WalletLibError::InvalidArgument { argument, reason }) => (argument, reason),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn it_encodes_a_function_call() {
+    let instruction = build_call_function_instruction(
+        TemplateAddress::from([1u8; 32]),
+        &template_def(),
+        "new",
+        vec![json!("my counter"), json!([10, null])],
+    )
+    .unwrap();
+
+    let expected_limits = tari_bor::encode(&(10u32, None::<u64>)).unwrap();
+    match instruction {
+        Instruction::CallFunction { function, args, .. } => {
+            assert_eq!(function, "new");
+            assert_eq!(args[0], args!["my counter".to_string()][0]);
+            assert_eq!(args[1], tari_template_lib::args::Arg::Literal(expected_limits));
+        },
+        other => panic!("unexpected instruction {:?}", other),
+    }
+}
+
+#[test]
+fn it_encodes_a_method_call_with_workspace_values() {
+    let instruction = build_call_method_instruction(
+        account_address(),
+        &template_def(),
+        "deposit",
+        vec![json!({ "workspace": "bucket" }), json!(100)],
+    )
+    .unwrap();
+
+    match instruction {
+        Instruction::CallMethod { args, .. } => assert_eq!(args, args![Workspace("bucket"), Amount::new(100)]),
+        other => panic!("unexpected instruction {:?}", other),
+    }
+}
+
+#[test]
+fn it_reports_arity_and_type_mismatches() {
+    let (argument, reason) = invalid_argument(build_call_function_instruction(
+        TemplateAddress::from([1u8; 32]),
+        &template_def(),
+        "new",
+        vec![json!("my counter")],
+    ));
+    assert_eq!(argument, "new");
+    assert_eq!(reason, "expected 2 arguments but got 1");
+
+    let (argument, reason) = invalid_argument(build_call_function_instruction(
+        TemplateAddress::from([1u8; 32]),
+        &template_def(),
+        "new",
+        vec![json!("my counter"), json!([-1, null])],
+    ));
+    assert_eq!(argument, "new.limits[0]");
+    assert_eq!(reason, "-1 is out of range for u32");
+
+    let (argument, _) = invalid_argument(build_call_method_instruction(
+        account_address(),
+        &template_def(),
+        "deposit",
+        vec![json!("bucket"), json!(100)],
+    ));
+    assert_eq!(argument, "deposit.bucket");
+}
+
+#[test]
+fn it_only_takes_buckets_and_proofs_from_the_workspace() {
+    let (argument, reason) = invalid_argument(build_call_method_instruction(
+        account_address(),
+        &template_def(),
+        "deposit",
+        vec![json!({ "workspace": "bucket" }), json!({ "workspace": "fee" })],
+    ));
+    assert_eq!(argument, "deposit.fee");
+    assert_eq!(reason, "expected Amount but got an object");
+
+    // an object that looks like a workspace reference is data for other types
+    let instruction = build_call_method_instruction(
+        account_address(),
+        &template_def(),
+        "set_metadata",
+        vec![json!({ "workspace": "bucket" })],
+    )
+    .unwrap();
+    match instruction {
+        Instruction::CallMethod { args, .. } => assert!(!matches!(args[0], Arg::Workspace(_))),
+        other => panic!("unexpected instruction {:?}", other),
+    }
+}
+
+#[test]
+fn it_rejects_calling_a_method_as_a_function() {
+    let (argument, _) = invalid_argument(build_call_function_instruction(
+        TemplateAddress::from([1u8; 32]),
+        &template_def(),
+        "deposit",
+        vec![json!({ "workspace": "bucket" }), json!(100)],
+    ));
+    assert_eq!(argument, "deposit");
+}
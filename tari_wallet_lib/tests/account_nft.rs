@@ -0,0 +1,59 @@
+mod common;
+
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args::Arg;
+use tari_template_lib::prelude::Metadata;
+use tari_wallet_lib::account_nft::build_account_nft_mint;
+use tari_wallet_lib::component::{account_address_from_public_key, account_nft_address_from_public_key};
+use tari_wallet_lib::metadata::MetadataField;
+
+use common::{public_key, PUBLIC_KEY as OWNER_PUBLIC_KEY};
+
+fn owner() -> RistrettoPublicKey {
+    public_key(OWNER_PUBLIC_KEY)
+}
+
+fn metadata() -> Vec<MetadataField> {
+    vec![MetadataField {
+        key: "name".to_string(),
+        value: "Tari".to_string(),
+    }]
+}
+
+#[test]
+fn it_creates_the_component_and_mints_in_one_transaction() {
+    let mint = build_account_nft_mint(&owner(), true, metadata()).unwrap();
+
+    assert_eq!(mint.component_address, account_nft_address_from_public_key(&owner()));
+    assert_ne!(mint.component_address, account_address_from_public_key(&owner()));
+    assert_eq!(mint.instructions.len(), 4);
+    assert!(matches!(&mint.instructions[0], Instruction::CallFunction { function, .. } if function == "create"));
+    match &mint.instructions[1] {
+        Instruction::CallMethod {
+            component_address,
+            method,
+            args,
+        } => {
+            assert_eq!(*component_address, mint.component_address);
+            assert_eq!(method, "mint");
+            let mut expected = Metadata::new();
+            expected.insert("name".to_string(), "Tari".to_string());
+            assert_eq!(args, &vec![Arg::Literal(tari_bor::encode(&expected).unwrap())]);
+        },
+        instruction => panic!("unexpected instruction {:?}", instruction),
+    }
+    assert!(matches!(
+        &mint.instructions[3],
+        Instruction::CallMethod { component_address, method, .. }
+            if *component_address == account_address_from_public_key(&owner()) && method == "deposit"
+    ));
+}
+
+#[test]
+fn it_mints_into_an_existing_component() {
+    let mint = build_account_nft_mint(&owner(), false, metadata()).unwrap();
+
+    assert_eq!(mint.instructions.len(), 3);
+    assert!(matches!(&mint.instructions[0], Instruction::CallMethod { method, .. } if method == "mint"));
+}
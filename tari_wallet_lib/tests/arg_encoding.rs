@@ -0,0 +1,225 @@
+mod common;
+
+use std::str::FromStr;
+
+use serde_json::json;
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_crypto::tari_utilities::ByteArray;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::models::{TemplateAddress, VaultId};
+use tari_template_lib::prelude::{
+    Amount, ComponentAddress, Metadata, NonFungibleAddress, NonFungibleId, ResourceAddress, RistrettoPublicKeyBytes,
+};
+use tari_wallet_lib::arg_encoding::{encode_typed_args, TypedArg};
+use tari_wallet_lib::error::WalletLibError;
+
+use common::{PUBLIC_KEY, RESOURCE};
+
+const COMPONENT: &str = "component_0101010101010101010101010101010101010101010101010101010101010101";
+const VAULT: &str = "vault_0303030303030303030303030303030303030303030303030303030303030303";
+const TEMPLATE: &str = "0404040404040404040404040404040404040404040404040404040404040404";
+
+fn parse(json: &str) -> Vec<TypedArg> {
+    serde_json::from_str(json).unwrap()
+}
+
+fn encode(arg: serde_json::Value) -> Vec<Arg> {
+    encode_typed_args(vec![serde_json::from_value(arg).unwrap()]).unwrap()
+}
+
+fn resource() -> ResourceAddress {
+    ResourceAddress::from_str(RESOURCE).unwrap()
+}
+
+#[test]
+fn it_encodes_like_the_args_macro() {
+    let args = encode_typed_args(parse(
+        r#"[{ "type": "Amount", "value": 100 }, { "type": "Workspace", "value": "bucket" }]"#,
+    ))
+    .unwrap();
+
+    assert_eq!(args, args![Amount::new(100), Workspace("bucket")]);
+}
+
+#[test]
+fn it_encodes_nested_values() {
+    let args = encode_typed_args(parse(
+        r#"[{
+            "type": "Tuple",
+            "value": [
+                { "type": "String", "value": "name" },
+                { "type": "Option", "value": { "type": "U32", "value": 7 } },
+                { "type": "Vec", "value": [{ "type": "Bool", "value": true }, { "type": "Bool", "value": false }] }
+            ]
+        }]"#,
+    ))
+    .unwrap();
+
+    let expected = tari_bor::encode(&("name".to_string(), Some(7u32), vec![true, false])).unwrap();
+    assert_eq!(args, vec![Arg::Literal(expected)]);
+}
+
+#[test]
+fn it_reports_the_path_of_invalid_values() {
+    let result = encode_typed_args(parse(
+        r#"[{ "type": "U8", "value": 1 }, { "type": "Tuple", "value": [{ "type": "ResourceAddress", "value": "nope" }] }]"#,
+    ));
+
+    match result {
+        Err(WalletLibError::InvalidArgument { argument, .. }) => assert_eq!(argument, "args[1][0]"),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn it_rejects_nested_workspace_references() {
+    let result = encode_typed_args(parse(
+        r#"[{ "type": "Vec", "value": [{ "type": "Workspace", "value": "bucket" }] }]"#,
+    ));
+    assert!(matches!(result, Err(WalletLibError::InvalidArgument { .. })));
+}
+
+#[test]
+fn it_rejects_mixed_vec_elements() {
+    let result = encode_typed_args(parse(
+        r#"[{ "type": "Vec", "value": [{ "type": "U8", "value": 1 }, { "type": "String", "value": "a" }] }]"#,
+    ));
+    assert!(matches!(result, Err(WalletLibError::InvalidArgument { .. })));
+}
+
+#[test]
+fn it_encodes_addresses_like_the_args_macro() {
+    let component = ComponentAddress::from_str(COMPONENT).unwrap();
+    let non_fungible = NonFungibleAddress::new(resource(), NonFungibleId::from_string("nft"));
+
+    assert_eq!(
+        encode(json!({ "type": "ComponentAddress", "value": COMPONENT })),
+        args![component]
+    );
+    assert_eq!(encode(json!({ "type": "ResourceAddress", "value": RESOURCE })), args![resource()]);
+    assert_eq!(
+        encode(json!({ "type": "VaultId", "value": VAULT })),
+        args![VaultId::from_str(VAULT).unwrap()]
+    );
+    assert_eq!(
+        encode(json!({ "type": "NonFungibleAddress", "value": format!("{} str:nft", RESOURCE) })),
+        args![non_fungible]
+    );
+    assert_eq!(
+        encode(json!({ "type": "TemplateAddress", "value": TEMPLATE })),
+        args![TemplateAddress::from_hex(TEMPLATE).unwrap()]
+    );
+}
+
+#[test]
+fn substate_ids_are_encoded_as_their_address_type() {
+    assert_eq!(
+        encode(json!({ "type": "SubstateId", "value": COMPONENT })),
+        args![ComponentAddress::from_str(COMPONENT).unwrap()]
+    );
+    assert_eq!(encode(json!({ "type": "SubstateId", "value": RESOURCE })), args![resource()]);
+    assert_eq!(
+        encode(json!({ "type": "SubstateId", "value": VAULT })),
+        args![VaultId::from_str(VAULT).unwrap()]
+    );
+}
+
+#[test]
+fn it_encodes_every_non_fungible_id_variant() {
+    let ids = [
+        ("str:nft", NonFungibleId::from_string("nft")),
+        ("u32:4294967295", NonFungibleId::from_u32(u32::MAX)),
+        ("u64:18446744073709551615", NonFungibleId::from_u64(u64::MAX)),
+        (
+            "uuid:0505050505050505050505050505050505050505050505050505050505050505",
+            NonFungibleId::from_u256([5; 32]),
+        ),
+    ];
+    for (canonical, id) in ids {
+        assert_eq!(
+            encode(json!({ "type": "NonFungibleId", "value": canonical })),
+            args![id.clone()],
+            "{}",
+            canonical
+        );
+        assert_eq!(id.to_canonical_string(), canonical);
+    }
+}
+
+#[test]
+fn it_encodes_public_keys_metadata_and_bytes() {
+    let public_key = RistrettoPublicKey::from_hex(PUBLIC_KEY).unwrap();
+    assert_eq!(
+        encode(json!({ "type": "PublicKey", "value": PUBLIC_KEY })),
+        args![RistrettoPublicKeyBytes::from_bytes(public_key.as_bytes()).unwrap()]
+    );
+
+    let mut metadata = Metadata::new();
+    metadata.insert("name".to_string(), "Tari".to_string());
+    assert_eq!(
+        encode(json!({ "type": "Metadata", "value": [{ "key": "name", "value": "Tari" }] })),
+        args![metadata]
+    );
+
+    assert_eq!(
+        encode(json!({ "type": "Bytes", "value": [0, 1, 255] })),
+        args![vec![0u8, 1, 255]]
+    );
+}
+
+#[test]
+fn it_encodes_integers_at_their_bounds() {
+    assert_eq!(encode(json!({ "type": "U8", "value": u8::MAX })), args![u8::MAX]);
+    assert_eq!(encode(json!({ "type": "U16", "value": u16::MAX })), args![u16::MAX]);
+    assert_eq!(encode(json!({ "type": "U32", "value": u32::MAX })), args![u32::MAX]);
+    assert_eq!(encode(json!({ "type": "U64", "value": u64::MAX })), args![u64::MAX]);
+    assert_eq!(encode(json!({ "type": "U64", "value": 0 })), args![0u64]);
+    assert_eq!(encode(json!({ "type": "I8", "value": i8::MIN })), args![i8::MIN]);
+    assert_eq!(encode(json!({ "type": "I8", "value": i8::MAX })), args![i8::MAX]);
+    assert_eq!(encode(json!({ "type": "I16", "value": i16::MIN })), args![i16::MIN]);
+    assert_eq!(encode(json!({ "type": "I16", "value": i16::MAX })), args![i16::MAX]);
+    assert_eq!(encode(json!({ "type": "I32", "value": i32::MIN })), args![i32::MIN]);
+    assert_eq!(encode(json!({ "type": "I32", "value": i32::MAX })), args![i32::MAX]);
+    assert_eq!(encode(json!({ "type": "I64", "value": i64::MIN })), args![i64::MIN]);
+    assert_eq!(encode(json!({ "type": "I64", "value": i64::MAX })), args![i64::MAX]);
+    assert_eq!(encode(json!({ "type": "Amount", "value": i64::MAX })), args![Amount::new(i64::MAX)]);
+
+    // values out of range are rejected when parsing
+    assert!(serde_json::from_value::<TypedArg>(json!({ "type": "U8", "value": 256 })).is_err());
+    assert!(serde_json::from_value::<TypedArg>(json!({ "type": "I8", "value": -129 })).is_err());
+}
+
+#[test]
+fn it_rejects_nested_vecs_of_mixed_types() {
+    let result = encode_typed_args(parse(
+        r#"[{ "type": "Vec", "value": [
+            { "type": "Vec", "value": [{ "type": "U8", "value": 1 }] },
+            { "type": "Vec", "value": [{ "type": "String", "value": "a" }] }
+        ] }]"#,
+    ));
+    assert!(matches!(result, Err(WalletLibError::InvalidArgument { .. })));
+
+    let result = encode_typed_args(parse(
+        r#"[{ "type": "Vec", "value": [
+            { "type": "Tuple", "value": [{ "type": "U8", "value": 1 }] },
+            { "type": "Tuple", "value": [{ "type": "U16", "value": 1 }] }
+        ] }]"#,
+    ));
+    assert!(matches!(result, Err(WalletLibError::InvalidArgument { .. })));
+}
+
+#[test]
+fn it_encodes_nested_vecs_of_the_same_type() {
+    let args = encode_typed_args(parse(
+        r#"[{ "type": "Vec", "value": [
+            { "type": "Vec", "value": [{ "type": "U8", "value": 1 }, { "type": "U8", "value": 2 }] },
+            { "type": "Vec", "value": [] },
+            { "type": "Vec", "value": [{ "type": "U8", "value": 3 }] }
+        ] }]"#,
+    ))
+    .unwrap();
+
+    assert_eq!(args, args![vec![vec![1u8, 2], vec![], vec![3u8]]]);
+}
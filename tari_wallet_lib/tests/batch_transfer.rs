@@ -0,0 +1,195 @@
+mod common;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{Amount, ComponentAddress, ResourceAddress};
+use tari_wallet_lib::batch_transfer::{BatchTransfer, TransferItem, SPLIT_FUNCTION};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::error::WalletLibError;
+
+use common::{
+    public_key, OTHER_PUBLIC_KEY as RECIPIENT_PUBLIC_KEY, OTHER_RESOURCE as RESOURCE_B, PUBLIC_KEY as SOURCE_PUBLIC_KEY,
+    RESOURCE as RESOURCE_A, THIRD_PUBLIC_KEY as OTHER_RECIPIENT_PUBLIC_KEY,
+};
+
+const SPLIT_TEMPLATE: &str = "0303030303030303030303030303030303030303030303030303030303030303";
+
+fn item(destination_public_key: &str, resource_address: &str, amount: i64) -> TransferItem {
+    TransferItem {
+        destination_public_key: destination_public_key.to_string(),
+        resource_address: resource_address.to_string(),
+        amount,
+        create_account: false,
+    }
+}
+
+fn resource(address: &str) -> ResourceAddress {
+    ResourceAddress::from_str(address).unwrap()
+}
+
+fn account(hex: &str) -> ComponentAddress {
+    account_address_from_public_key(&public_key(hex))
+}
+
+fn new_batch(items: Vec<TransferItem>) -> Result<BatchTransfer, WalletLibError> {
+    BatchTransfer::new(&public_key(SOURCE_PUBLIC_KEY), items)
+}
+
+fn instructions(batch: &BatchTransfer) -> Vec<Instruction> {
+    batch.instructions(account(SOURCE_PUBLIC_KEY), TemplateAddress::from_hex(SPLIT_TEMPLATE).unwrap())
+}
+
+fn methods(instructions: &[Instruction]) -> Vec<String> {
+    instructions
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::CallMethod { method, .. } => method.clone(),
+            Instruction::CallFunction { function, .. } => function.clone(),
+            Instruction::CreateAccount { .. } => "create_account".to_string(),
+            Instruction::PutLastInstructionOutputOnWorkspace { .. } => "put_on_workspace".to_string(),
+            _ => "other".to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn it_merges_transfers_to_the_same_recipient() {
+    let batch = new_batch(vec![
+        item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 100),
+        item(OTHER_RECIPIENT_PUBLIC_KEY, RESOURCE_A, 10),
+        item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 50),
+        item(RECIPIENT_PUBLIC_KEY, RESOURCE_B, 5),
+    ])
+    .unwrap();
+
+    assert_eq!(batch.resource_totals(), &[
+        (resource(RESOURCE_A), 160),
+        (resource(RESOURCE_B), 5)
+    ]);
+}
+
+#[test]
+fn it_withdraws_once_per_resource_and_splits_the_bucket() {
+    let batch = new_batch(vec![
+        item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 100),
+        item(OTHER_RECIPIENT_PUBLIC_KEY, RESOURCE_A, 10),
+        item(RECIPIENT_PUBLIC_KEY, RESOURCE_B, 5),
+    ])
+    .unwrap();
+
+    let instructions = instructions(&batch);
+    assert_eq!(methods(&instructions), vec![
+        "withdraw",
+        "put_on_workspace",
+        SPLIT_FUNCTION,
+        "withdraw",
+        "put_on_workspace",
+        SPLIT_FUNCTION,
+    ]);
+    assert!(matches!(
+        &instructions[0],
+        Instruction::CallMethod { component_address, args, .. }
+            if *component_address == account(SOURCE_PUBLIC_KEY)
+                && *args == args![resource(RESOURCE_A), Amount::new(110)]
+    ));
+    match &instructions[2] {
+        Instruction::CallFunction {
+            template_address,
+            args,
+            ..
+        } => {
+            assert_eq!(*template_address, TemplateAddress::from_hex(SPLIT_TEMPLATE).unwrap());
+            let deposits = vec![
+                (account(RECIPIENT_PUBLIC_KEY), Amount::new(100)),
+                (account(OTHER_RECIPIENT_PUBLIC_KEY), Amount::new(10)),
+            ];
+            assert_eq!(args[0], Arg::Workspace(b"bucket_0".to_vec()));
+            assert_eq!(args[1..], args![deposits][..]);
+        },
+        instruction => panic!("unexpected instruction {:?}", instruction),
+    }
+}
+
+#[test]
+fn it_creates_missing_accounts_once() {
+    let mut first = item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 100);
+    first.create_account = true;
+    let batch = new_batch(vec![first, item(RECIPIENT_PUBLIC_KEY, RESOURCE_B, 5)]).unwrap();
+
+    let instructions = instructions(&batch);
+    assert_eq!(methods(&instructions)[0], "create_account");
+    assert_eq!(methods(&instructions).iter().filter(|m| *m == "create_account").count(), 1);
+}
+
+#[test]
+fn it_checks_the_balance_of_each_resource() {
+    let batch = new_batch(vec![
+        item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 100),
+        item(OTHER_RECIPIENT_PUBLIC_KEY, RESOURCE_A, 100),
+    ])
+    .unwrap();
+
+    let mut balances = HashMap::new();
+    balances.insert(resource(RESOURCE_A), 200);
+    balances.insert(XTR, 10);
+    assert!(batch.check_balances(&balances, 10).is_ok());
+
+    balances.insert(resource(RESOURCE_A), 150);
+    assert_eq!(
+        batch.check_balances(&balances, 10),
+        Err(WalletLibError::InsufficientFunds {
+            requested: 200,
+            available: 150,
+            shortfall: 50,
+        })
+    );
+}
+
+#[test]
+fn it_counts_the_fee_in_the_xtr_total() {
+    let batch = new_batch(vec![item(RECIPIENT_PUBLIC_KEY, &XTR.to_string(), 100)]).unwrap();
+    let balances = HashMap::from([(XTR, 120)]);
+
+    assert!(batch.check_balances(&balances, 20).is_ok());
+    assert_eq!(
+        batch.check_balances(&balances, 25),
+        Err(WalletLibError::InsufficientFunds {
+            requested: 125,
+            available: 120,
+            shortfall: 5,
+        })
+    );
+
+    // the fee is checked even when no XTR is sent
+    let batch = new_batch(vec![item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 100)]).unwrap();
+    let balances = HashMap::from([(resource(RESOURCE_A), 100)]);
+    assert!(matches!(
+        batch.check_balances(&balances, 20),
+        Err(WalletLibError::InsufficientFunds { requested: 20, .. })
+    ));
+}
+
+#[test]
+fn it_rejects_invalid_transfers() {
+    assert!(new_batch(vec![]).is_err());
+    assert_eq!(
+        new_batch(vec![item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 1), item(RECIPIENT_PUBLIC_KEY, RESOURCE_A, 0)])
+            .unwrap_err(),
+        WalletLibError::invalid_argument("transfers[1].amount", "the amount must be positive")
+    );
+    assert!(matches!(
+        new_batch(vec![item("not a key", RESOURCE_A, 1)]),
+        Err(WalletLibError::InvalidArgument { .. })
+    ));
+    assert_eq!(
+        new_batch(vec![item(SOURCE_PUBLIC_KEY, RESOURCE_A, 1)]).unwrap_err(),
+        WalletLibError::invalid_argument("transfers[0].destination_public_key", "cannot transfer to the source account")
+    );
+}
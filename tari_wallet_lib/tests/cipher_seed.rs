@@ -0,0 +1,55 @@
+use tari_wallet_lib::cipher_seed::{CipherSeed, CipherSeedError};
+
+// Each word holds 11 bits of the seed, least significant bit first: "abandon" is 0 and "able" is 2, so this is a
+// seed of version 2 followed by zeros, whose checksum does not match
+const BAD_CHECKSUM: &str = "able abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                            abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                            abandon";
+
+fn words(count: usize) -> String {
+    vec!["abandon"; count].join(" ")
+}
+
+#[test]
+fn it_rejects_a_wrong_word_count() {
+    assert_eq!(
+        CipherSeed::from_mnemonic(&words(23), None).err(),
+        Some(CipherSeedError::InvalidWordCount(23))
+    );
+    assert_eq!(
+        CipherSeed::from_mnemonic(&words(25), None).err(),
+        Some(CipherSeedError::InvalidWordCount(25))
+    );
+}
+
+#[test]
+fn it_rejects_unknown_words() {
+    let mnemonic = format!("tari {}", words(23));
+    assert_eq!(
+        CipherSeed::from_mnemonic(&mnemonic, None).err(),
+        Some(CipherSeedError::UnknownWord("tari".to_string()))
+    );
+}
+
+#[test]
+fn it_rejects_unsupported_versions() {
+    assert_eq!(
+        CipherSeed::from_mnemonic(&words(24), None).err(),
+        Some(CipherSeedError::UnsupportedVersion(0))
+    );
+}
+
+#[test]
+fn it_rejects_a_bad_checksum() {
+    assert_eq!(
+        CipherSeed::from_mnemonic(BAD_CHECKSUM, None).err(),
+        Some(CipherSeedError::ChecksumMismatch)
+    );
+
+    // the words are case insensitive and may be separated by any whitespace
+    let mnemonic = BAD_CHECKSUM.to_uppercase().replace(' ', "\n ");
+    assert_eq!(
+        CipherSeed::from_mnemonic(&mnemonic, None).err(),
+        Some(CipherSeedError::ChecksumMismatch)
+    );
+}
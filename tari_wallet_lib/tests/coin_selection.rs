@@ -0,0 +1,151 @@
+use tari_common_types::types::{CommitmentFactory, PrivateKey};
+use tari_crypto::commitment::HomomorphicCommitmentFactory;
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_dan_wallet_crypto::encrypt_value_and_mask;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::models::{ObjectKey, ResourceAddress};
+use tari_wallet_lib::coin_selection::{select_outputs, CoinSelectionStrategy, InsufficientFundsError};
+use tari_wallet_lib::confidential_transfer::{ConfidentialOutputModel, OutputStatus, ACCOUNT_KEY_INDEX};
+
+const STRATEGIES: [CoinSelectionStrategy; 4] = [
+    CoinSelectionStrategy::LargestFirst,
+    CoinSelectionStrategy::SmallestFirst,
+    CoinSelectionStrategy::MinimizeChange,
+    CoinSelectionStrategy::Random,
+];
+
+// only the value and status of an output matter for the selection
+fn output(value: u64, status: OutputStatus) -> ConfidentialOutputModel {
+    let mask = PrivateKey::random(&mut rand::thread_rng());
+    let (nonce, public_nonce) = RistrettoPublicKey::random_keypair(&mut rand::thread_rng());
+    let (_, public_key) = RistrettoPublicKey::random_keypair(&mut rand::thread_rng());
+    let address = SubstateId::Resource(ResourceAddress::new(ObjectKey::default()));
+
+    ConfidentialOutputModel {
+        account_address: address.clone(),
+        vault_address: address,
+        commitment: CommitmentFactory::default().commit_value(&mask, value),
+        value,
+        sender_public_nonce: Some(public_nonce),
+        encryption_secret_key_index: ACCOUNT_KEY_INDEX,
+        encrypted_data: encrypt_value_and_mask(value, &mask, &public_key, &nonce).unwrap(),
+        public_asset_tag: None,
+        status,
+        locked_by_proof: None,
+    }
+}
+
+fn unspent(values: &[u64]) -> Vec<ConfidentialOutputModel> {
+    values.iter().map(|v| output(*v, OutputStatus::Unspent)).collect()
+}
+
+fn selected_values(values: &[u64], amount: u64, strategy: CoinSelectionStrategy) -> Vec<u64> {
+    let (selected, total) = select_outputs(unspent(values), amount, strategy).unwrap();
+    let mut selected: Vec<u64> = selected.iter().map(|o| o.value).collect();
+    assert_eq!(selected.iter().sum::<u64>(), total);
+    selected.sort_unstable();
+    selected
+}
+
+// smallest total of any subset of `values` that covers `amount`
+fn best_total(values: &[u64], amount: u64) -> u64 {
+    (0..1u32 << values.len())
+        .map(|subset| {
+            values
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| subset & (1 << i) != 0)
+                .map(|(_, v)| v)
+                .sum::<u64>()
+        })
+        .filter(|total| *total >= amount)
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn largest_first_spends_the_largest_outputs() {
+    assert_eq!(
+        selected_values(&[10, 20, 50, 100], 60, CoinSelectionStrategy::LargestFirst),
+        vec![100]
+    );
+    assert_eq!(
+        selected_values(&[10, 20, 50, 100], 120, CoinSelectionStrategy::LargestFirst),
+        vec![50, 100]
+    );
+}
+
+#[test]
+fn smallest_first_spends_the_smallest_outputs() {
+    assert_eq!(
+        selected_values(&[10, 20, 50, 100], 60, CoinSelectionStrategy::SmallestFirst),
+        vec![10, 20, 50]
+    );
+}
+
+#[test]
+fn every_strategy_covers_the_amount_without_spent_outputs() {
+    let mut outputs = unspent(&[10, 20, 50, 100]);
+    outputs.push(output(1000, OutputStatus::Spent));
+    outputs.push(output(1000, OutputStatus::Locked));
+
+    for strategy in STRATEGIES {
+        let (selected, total) = select_outputs(outputs.clone(), 75, strategy).unwrap();
+        assert!(total >= 75, "{:?} selected {}", strategy, total);
+        assert!(selected.iter().all(|o| matches!(o.status, OutputStatus::Unspent)));
+    }
+}
+
+#[test]
+fn minimize_change_finds_the_exact_amount() {
+    // spending the largest outputs first would select 4 + 3
+    assert_eq!(selected_values(&[3, 3, 4], 6, CoinSelectionStrategy::MinimizeChange), vec![3, 3]);
+    assert_eq!(
+        selected_values(&[1, 5, 8, 9, 16], 22, CoinSelectionStrategy::MinimizeChange),
+        vec![1, 5, 16]
+    );
+}
+
+#[test]
+fn minimize_change_is_optimal() {
+    let sets: [&[u64]; 4] = [&[3, 3, 4], &[1, 5, 8, 9, 13], &[7, 11, 13, 17, 19, 23], &[2, 2, 2, 9, 40, 41]];
+    for values in sets {
+        let balance = values.iter().sum::<u64>();
+        for amount in 1..=balance {
+            let selected = selected_values(values, amount, CoinSelectionStrategy::MinimizeChange);
+            assert_eq!(
+                selected.iter().sum::<u64>(),
+                best_total(values, amount),
+                "outputs {:?} and amount {}",
+                values,
+                amount
+            );
+        }
+    }
+}
+
+#[test]
+fn nothing_is_selected_for_a_zero_amount() {
+    for strategy in STRATEGIES {
+        assert!(selected_values(&[10, 20], 0, strategy).is_empty());
+    }
+}
+
+#[test]
+fn it_reports_the_shortfall_of_insufficient_funds() {
+    let mut outputs = unspent(&[10, 20]);
+    outputs.push(output(100, OutputStatus::Spent));
+
+    for strategy in STRATEGIES {
+        let error = select_outputs(outputs.clone(), 45, strategy).unwrap_err();
+        assert!(matches!(
+            error,
+            InsufficientFundsError {
+                requested: 45,
+                available: 30,
+                shortfall: 15,
+            }
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+//! Fixtures shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_engine_types::commit_result::{FinalizeResult, RejectReason, TransactionResult};
+
+/// Secret key of the account signing the transactions, `PUBLIC_KEY` is its public key
+pub const SECRET_KEY: &str = "bd7e71ef67228d750e165f44d4a0dbdaf2e5c27881dcc6714bdd6c5e3a67d402";
+pub const PUBLIC_KEY: &str = "38c0ecd5009b1a09becbaf71d03cdb5cfbe9435b8b69a1672b3bb7ed6060673a";
+/// Public keys of other accounts, e.g. the recipients of a transfer
+pub const OTHER_PUBLIC_KEY: &str = "6e29fee6082e238475d40c6d4bed1d265ed5cd9d735a84a0380c09ff2d2e8d0d";
+pub const THIRD_PUBLIC_KEY: &str = "d6b3ddc2d8edbaa4fefe9832c2849fcb126b1d7e322f60b1705eb70c07e0bf65";
+
+pub const RESOURCE: &str = "resource_0101010101010101010101010101010101010101010101010101010101010101";
+pub const OTHER_RESOURCE: &str = "resource_0202020202020202020202020202020202020202020202020202020202020202";
+
+pub fn secret_key() -> RistrettoSecretKey {
+    RistrettoSecretKey::from_hex(SECRET_KEY).unwrap()
+}
+
+pub fn public_key(hex: &str) -> RistrettoPublicKey {
+    RistrettoPublicKey::from_hex(hex).unwrap()
+}
+
+/// Result of a dry run with the given outcome, as returned by the indexer
+pub fn finalize_result(result: TransactionResult) -> FinalizeResult {
+    let mut finalize_result = FinalizeResult::new_rejected(Default::default(), RejectReason::Unknown);
+    finalize_result.result = result;
+    finalize_result
+}
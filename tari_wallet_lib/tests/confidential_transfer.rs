@@ -0,0 +1,208 @@
+mod common;
+
+use std::collections::HashMap;
+
+use tari_common_types::types::{CommitmentFactory, PrivateKey};
+use tari_crypto::commitment::HomomorphicCommitmentFactory;
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_dan_wallet_crypto::encrypt_value_and_mask;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::models::{Amount, ObjectKey, ResourceAddress};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::coin_selection::CoinSelectionStrategy;
+use tari_wallet_lib::confidential_transfer::{
+    create_change_output, next_key, output_mask, parse_output_key_indexes, select_inputs, ConfidentialOutputModel,
+    ConfidentialTransferInputSelection, OutputKeyIndex, OutputStatus, SelectedInputs, ACCOUNT_KEY_INDEX,
+};
+use tari_wallet_lib::crypto::derive_output_key;
+use tari_wallet_lib::error::WalletLibError;
+
+use common::secret_key as account_key;
+
+fn account_address() -> SubstateId {
+    SubstateId::Component(account_address_from_public_key(&RistrettoPublicKey::from_secret_key(
+        &account_key(),
+    )))
+}
+
+fn vault_address() -> SubstateId {
+    SubstateId::Resource(ResourceAddress::new(ObjectKey::default()))
+}
+
+// an output sent to the account by someone else, as found in its vault
+fn received_output(value: u64, status: OutputStatus) -> (PrivateKey, ConfidentialOutputModel) {
+    let mask = PrivateKey::random(&mut rand::thread_rng());
+    let (nonce, public_nonce) = RistrettoPublicKey::random_keypair(&mut rand::thread_rng());
+    let account_public_key = RistrettoPublicKey::from_secret_key(&account_key());
+    let encrypted_data = encrypt_value_and_mask(value, &mask, &account_public_key, &nonce).unwrap();
+
+    let output = ConfidentialOutputModel {
+        account_address: account_address(),
+        vault_address: vault_address(),
+        commitment: CommitmentFactory::default().commit_value(&mask, value),
+        value,
+        sender_public_nonce: Some(public_nonce),
+        encryption_secret_key_index: ACCOUNT_KEY_INDEX,
+        encrypted_data,
+        public_asset_tag: None,
+        status,
+        locked_by_proof: None,
+    };
+    (mask, output)
+}
+
+// outputs of 30 and 70 plus a spent output that must never be selected
+fn mixed_outputs() -> Vec<ConfidentialOutputModel> {
+    vec![
+        received_output(30, OutputStatus::Unspent).1,
+        received_output(500, OutputStatus::Spent).1,
+        received_output(70, OutputStatus::Unspent).1,
+    ]
+}
+
+fn select(
+    spend_amount: i64,
+    input_selection: ConfidentialTransferInputSelection,
+) -> Result<SelectedInputs, WalletLibError> {
+    select_inputs(
+        mixed_outputs(),
+        Amount::new(50),
+        Amount::new(spend_amount),
+        input_selection,
+        CoinSelectionStrategy::LargestFirst,
+    )
+}
+
+fn confidential_values(selected: &SelectedInputs) -> Vec<u64> {
+    selected.confidential.iter().map(|o| o.value).collect()
+}
+
+fn is_insufficient_funds<T>(result: Result<T, WalletLibError>) -> bool {
+    matches!(result, Err(WalletLibError::InsufficientFunds { .. }))
+}
+
+#[test]
+fn next_key_derives_the_key_of_its_index() {
+    let (index, key) = next_key(&account_key()).unwrap();
+
+    assert_ne!(index, ACCOUNT_KEY_INDEX);
+    assert_eq!(key, derive_output_key(&account_key(), index).unwrap());
+    assert_ne!(key, account_key());
+}
+
+#[test]
+fn change_outputs_round_trip_through_their_key_index() {
+    let (statement, output) = create_change_output(
+        &account_key(),
+        account_address(),
+        vault_address(),
+        Amount::new(250),
+        None,
+    )
+    .unwrap();
+
+    assert_ne!(output.encryption_secret_key_index, ACCOUNT_KEY_INDEX);
+    assert_eq!(output.value, 250);
+    assert_eq!(
+        derive_output_key(&account_key(), output.encryption_secret_key_index).unwrap(),
+        statement.mask
+    );
+    assert_eq!(output_mask(&account_key(), &output).unwrap(), statement.mask);
+}
+
+#[test]
+fn outputs_with_a_wrong_key_index_are_rejected() {
+    let (_, mut output) = create_change_output(
+        &account_key(),
+        account_address(),
+        vault_address(),
+        Amount::new(250),
+        None,
+    )
+    .unwrap();
+    let (other_index, _) = next_key(&account_key()).unwrap();
+    output.encryption_secret_key_index = other_index;
+
+    assert!(output_mask(&account_key(), &output).is_err());
+}
+
+#[test]
+fn stored_key_indexes_recover_the_mask_of_change_outputs() {
+    let (statement, output) = create_change_output(
+        &account_key(),
+        account_address(),
+        vault_address(),
+        Amount::new(250),
+        None,
+    )
+    .unwrap();
+
+    // the snap stores the key index as a decimal string, since most indexes do not fit in a JS number
+    let stored = OutputKeyIndex::new(&output);
+    assert_eq!(stored.key_index, output.encryption_secret_key_index.to_string());
+    let mut stored_indexes = HashMap::new();
+    stored_indexes.insert(stored.commitment.clone(), stored.key_index);
+    let indexes = parse_output_key_indexes(stored_indexes).unwrap();
+
+    // as found again in the vault, before the key index is looked up
+    let mut found = output.clone();
+    found.encryption_secret_key_index = indexes[&stored.commitment];
+    assert_eq!(found.encryption_secret_key_index, output.encryption_secret_key_index);
+    assert_eq!(output_mask(&account_key(), &found).unwrap(), statement.mask);
+}
+
+#[test]
+fn invalid_stored_key_indexes_are_rejected() {
+    let mut indexes = HashMap::new();
+    indexes.insert("00".repeat(32), "-1".to_string());
+
+    assert!(matches!(
+        parse_output_key_indexes(indexes),
+        Err(WalletLibError::InvalidArgument { .. })
+    ));
+}
+
+#[test]
+fn legacy_outputs_are_decrypted_with_the_account_key() {
+    // outputs found in a vault are encrypted by the sender to the account public key and recorded with index 0
+    let (mask, output) = received_output(100, OutputStatus::Unspent);
+
+    assert_eq!(output_mask(&account_key(), &output).unwrap(), mask);
+}
+
+#[test]
+fn revealed_only_spends_the_revealed_funds() {
+    let selected = select(40, ConfidentialTransferInputSelection::RevealedOnly).unwrap();
+    assert!(selected.confidential.is_empty());
+    assert_eq!(selected.revealed, Amount::new(40));
+
+    // the confidential outputs are not used even when they could cover the rest
+    assert!(is_insufficient_funds(select(60, ConfidentialTransferInputSelection::RevealedOnly)));
+}
+
+#[test]
+fn prefer_revealed_covers_the_rest_with_confidential_outputs() {
+    let selected = select(40, ConfidentialTransferInputSelection::PreferRevealed).unwrap();
+    assert!(selected.confidential.is_empty());
+    assert_eq!(selected.revealed, Amount::new(40));
+
+    let selected = select(80, ConfidentialTransferInputSelection::PreferRevealed).unwrap();
+    assert_eq!(selected.revealed, Amount::new(50));
+    assert_eq!(confidential_values(&selected), vec![70]);
+
+    assert!(is_insufficient_funds(select(151, ConfidentialTransferInputSelection::PreferRevealed)));
+}
+
+#[test]
+fn prefer_confidential_covers_the_rest_with_revealed_funds() {
+    let selected = select(60, ConfidentialTransferInputSelection::PreferConfidential).unwrap();
+    assert_eq!(selected.revealed, Amount::zero());
+    assert_eq!(confidential_values(&selected), vec![70]);
+
+    let selected = select(120, ConfidentialTransferInputSelection::PreferConfidential).unwrap();
+    assert_eq!(selected.revealed, Amount::new(20));
+    assert_eq!(confidential_values(&selected), vec![70, 30]);
+
+    assert!(is_insufficient_funds(select(151, ConfidentialTransferInputSelection::PreferConfidential)));
+}
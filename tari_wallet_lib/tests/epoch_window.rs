@@ -0,0 +1,79 @@
+mod common;
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::epoch_window::EpochWindow;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::fee_estimation::rebuild_with_fee;
+use tari_wallet_lib::transaction_builder::TransactionBuilder;
+use tari_wallet_lib::transaction_summary::summarize_transaction;
+
+use common::secret_key;
+
+fn builder_paying_fees() -> TransactionBuilder {
+    let account_address = account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&secret_key()));
+    let mut builder = TransactionBuilder::new();
+    builder.pay_fee(&account_address.to_string(), 1000).unwrap();
+    builder
+}
+
+#[test]
+fn it_builds_a_window_from_the_current_epoch() {
+    assert_eq!(EpochWindow::from_current_epoch(100, 10), EpochWindow {
+        min_epoch: Some(100),
+        max_epoch: Some(110),
+    });
+    assert_eq!(EpochWindow::from_current_epoch(u64::MAX, 10).max_epoch, Some(u64::MAX));
+}
+
+#[test]
+fn it_rejects_inverted_windows() {
+    assert!(matches!(
+        EpochWindow::new(Some(11), Some(10)),
+        Err(WalletLibError::InvalidArgument { .. })
+    ));
+    assert!(EpochWindow::new(Some(10), Some(10)).is_ok());
+    assert!(EpochWindow::new(None, Some(10)).is_ok());
+}
+
+#[test]
+fn it_signs_the_window_into_the_transaction() {
+    let mut builder = builder_paying_fees();
+    builder.set_epoch_bounds(Some(5), Some(15)).unwrap();
+    builder.sign_with(&secret_key()).unwrap();
+    let transaction = builder.build_transaction().unwrap();
+
+    assert_eq!(EpochWindow::of_transaction(&transaction), EpochWindow {
+        min_epoch: Some(5),
+        max_epoch: Some(15),
+    });
+
+    let summary = summarize_transaction(&transaction, None);
+    assert_eq!(summary.min_epoch, Some(5));
+    assert_eq!(summary.max_epoch, Some(15));
+
+    let rebuilt = rebuild_with_fee(&transaction, 2000, &secret_key()).unwrap();
+    assert_eq!(
+        EpochWindow::of_transaction(&rebuilt),
+        EpochWindow::of_transaction(&transaction)
+    );
+}
+
+#[test]
+fn it_does_not_change_the_window_after_signing() {
+    let mut builder = builder_paying_fees();
+    builder.sign_with(&secret_key()).unwrap();
+    assert!(matches!(
+        builder.set_epoch_bounds(Some(5), None),
+        Err(WalletLibError::InvalidTransaction { .. })
+    ));
+}
+
+#[test]
+fn transactions_are_unbounded_by_default() {
+    let mut builder = builder_paying_fees();
+    builder.sign_with(&secret_key()).unwrap();
+    let transaction = builder.build_transaction().unwrap();
+    assert!(EpochWindow::of_transaction(&transaction).is_unbounded());
+}
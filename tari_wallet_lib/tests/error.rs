@@ -0,0 +1,44 @@
+use serde_json::json;
+use tari_wallet_lib::coin_selection::InsufficientFundsError;
+use tari_wallet_lib::component::get_account_address_from_public_key;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::keystore::KeystoreError;
+
+#[test]
+fn it_rejects_an_invalid_public_key_without_panicking() {
+    let result = get_account_address_from_public_key("not a public key");
+    assert!(matches!(result, Err(WalletLibError::InvalidPublicKey { .. })));
+}
+
+#[test]
+fn it_serializes_the_code_and_details() {
+    let error = WalletLibError::insufficient_funds(100, 40);
+    let serialized = serde_json::to_value(&error).unwrap();
+
+    assert_eq!(serialized, json!({
+        "code": "INSUFFICIENT_FUNDS",
+        "details": { "requested": 100, "available": 40, "shortfall": 60 }
+    }));
+    assert_eq!(serialized["code"], error.code());
+}
+
+#[test]
+fn it_keeps_the_shortfall_of_coin_selection() {
+    let error = WalletLibError::from(InsufficientFundsError {
+        requested: 45,
+        available: 30,
+        shortfall: 15,
+    });
+    assert_eq!(error, WalletLibError::insufficient_funds(45, 30));
+    assert_eq!(
+        error.to_string(),
+        "Insufficient funds: requested 45 but only 30 is available (short by 15)"
+    );
+}
+
+#[test]
+fn it_keeps_the_code_of_unit_variants() {
+    let error = WalletLibError::from(KeystoreError::DecryptionFailed);
+    assert_eq!(error, WalletLibError::WrongPassword);
+    assert_eq!(serde_json::to_value(&error).unwrap()["code"], "WRONG_PASSWORD");
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_engine_types::commit_result::{RejectReason, TransactionResult};
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::SubstateDiff;
+use tari_template_lib::args;
+use tari_template_lib::args::Arg;
+use tari_template_lib::prelude::{Amount, ComponentAddress};
+use tari_transaction::Transaction;
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::fee_estimation::{estimate_fee, rebuild_with_fee, FeeEstimate, FeeMargin};
+
+use common::{finalize_result, secret_key};
+
+fn account_address() -> ComponentAddress {
+    account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&secret_key()))
+}
+
+fn pay_fee(fee: i64) -> Instruction {
+    Instruction::CallMethod {
+        component_address: account_address(),
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(fee)],
+    }
+}
+
+fn build_transaction(fee_instructions: Vec<Instruction>) -> Transaction {
+    Transaction::builder()
+        .with_fee_instructions(fee_instructions)
+        .with_instructions(vec![Instruction::CreateAccount {
+            owner_public_key: RistrettoPublicKey::from_secret_key(&secret_key()),
+            workspace_bucket: None,
+        }])
+        .sign(&secret_key())
+        .build()
+}
+
+fn paid_fee(transaction: &Transaction) -> i64 {
+    match &transaction.fee_instructions()[0] {
+        Instruction::CallMethod { args, .. } => match &args[0] {
+            Arg::Literal(bytes) => tari_bor::decode::<Amount>(bytes).unwrap().value(),
+            arg => panic!("unexpected argument {:?}", arg),
+        },
+        instruction => panic!("unexpected instruction {:?}", instruction),
+    }
+}
+
+#[test]
+fn it_adds_the_margin_to_the_consumed_fee() {
+    let margin = FeeMargin {
+        percent: 10,
+        minimum: 0,
+    };
+    assert_eq!(FeeEstimate::new(1000, &margin), FeeEstimate {
+        fee_consumed: 1000,
+        margin: 100,
+        fee: 1100,
+    });
+    // the percentage is rounded up
+    assert_eq!(FeeEstimate::new(1001, &margin).fee, 1102);
+}
+
+#[test]
+fn it_applies_the_minimum_margin() {
+    let margin = FeeMargin {
+        percent: 10,
+        minimum: 50,
+    };
+    assert_eq!(FeeEstimate::new(100, &margin).fee, 150);
+    assert_eq!(FeeEstimate::new(i64::MAX, &FeeMargin::default()).fee, i64::MAX);
+}
+
+#[test]
+fn it_estimates_the_fee_of_accepted_dry_runs() {
+    let accepted = finalize_result(TransactionResult::Accept(SubstateDiff::new()));
+    let fee_consumed = accepted.fee_receipt.total_fees_charged().value();
+
+    assert_eq!(
+        estimate_fee(&accepted, &FeeMargin::default()).unwrap(),
+        FeeEstimate::new(fee_consumed, &FeeMargin::default())
+    );
+}
+
+#[test]
+fn it_rejects_dry_runs_that_did_not_run_every_instruction() {
+    let reason = RejectReason::ExecutionFailure("out of funds".to_string());
+    let fee_only = finalize_result(TransactionResult::AcceptFeeRejectRest(SubstateDiff::new(), reason.clone()));
+    let rejected = finalize_result(TransactionResult::Reject(reason));
+
+    for result in [fee_only, rejected] {
+        match estimate_fee(&result, &FeeMargin::default()) {
+            Err(WalletLibError::InvalidTransaction { reason }) => {
+                assert!(reason.contains("out of funds"), "{}", reason)
+            },
+            other => panic!("unexpected estimate {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn it_rebuilds_the_transaction_with_the_new_fee() {
+    let transaction = build_transaction(vec![pay_fee(1_000_000)]);
+
+    let rebuilt = rebuild_with_fee(&transaction, 1500, &secret_key()).unwrap();
+    assert_eq!(paid_fee(&rebuilt), 1500);
+    assert_eq!(rebuilt.instructions(), transaction.instructions());
+    assert_eq!(rebuilt.signatures().len(), 1);
+    assert_ne!(rebuilt.calculate_id(), transaction.calculate_id());
+}
+
+#[test]
+fn it_rejects_transactions_without_a_single_fee_payment() {
+    let no_fees = build_transaction(vec![]);
+    assert!(matches!(
+        rebuild_with_fee(&no_fees, 1500, &secret_key()),
+        Err(WalletLibError::InvalidTransaction { .. })
+    ));
+
+    let two_fees = build_transaction(vec![pay_fee(100), pay_fee(100)]);
+    assert!(matches!(
+        rebuild_with_fee(&two_fees, 1500, &secret_key()),
+        Err(WalletLibError::InvalidTransaction { .. })
+    ));
+}
+
+#[test]
+fn it_rejects_a_key_that_did_not_sign_the_transaction() {
+    let transaction = build_transaction(vec![pay_fee(100)]);
+    let other_key =
+        RistrettoSecretKey::from_hex("8569156e65b76e9c3dccf64d5b9dffa831f1436e5b03073915a680a15e3a9904").unwrap();
+
+    assert!(matches!(
+        rebuild_with_fee(&transaction, 1500, &other_key),
+        Err(WalletLibError::InvalidTransaction { .. })
+    ));
+}
+
+#[test]
+fn it_rejects_non_positive_fees() {
+    let transaction = build_transaction(vec![pay_fee(100)]);
+    assert!(matches!(
+        rebuild_with_fee(&transaction, 0, &secret_key()),
+        Err(WalletLibError::InvalidArgument { .. })
+    ));
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{Amount, ComponentAddress};
+use tari_transaction::Transaction;
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::epoch_window::EpochWindow;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::fee_sponsorship::{
+    build_sponsored_transaction, sponsor_transaction, transaction_signers, SponsorPolicy,
+};
+
+use common::secret_key as user_key;
+
+const SPONSOR_SECRET_KEY: &str = "1b9e5c4a2ab3f0d8d2c8a6b7b53c8d3e2f31e1a7e3d40d2a6d9b1d3a9e2c1a0b";
+
+fn sponsor_key() -> RistrettoSecretKey {
+    RistrettoSecretKey::from_hex(SPONSOR_SECRET_KEY).unwrap()
+}
+
+fn create_user_account() -> Vec<Instruction> {
+    vec![Instruction::CreateAccount {
+        owner_public_key: RistrettoPublicKey::from_secret_key(&user_key()),
+        workspace_bucket: None,
+    }]
+}
+
+fn user_account() -> ComponentAddress {
+    account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&user_key()))
+}
+
+fn sponsored_instructions(fee: i64, instructions: Vec<Instruction>) -> Transaction {
+    build_sponsored_transaction(
+        &user_key(),
+        &RistrettoPublicKey::from_secret_key(&sponsor_key()),
+        fee,
+        instructions,
+        vec![],
+        EpochWindow::default(),
+    )
+    .unwrap()
+}
+
+fn sponsored(fee: i64) -> Transaction {
+    sponsored_instructions(fee, create_user_account())
+}
+
+fn max_fee(fee: i64) -> SponsorPolicy {
+    SponsorPolicy {
+        max_fee: Some(fee),
+        ..Default::default()
+    }
+}
+
+fn is_invalid_transaction<T>(result: Result<T, WalletLibError>) -> bool {
+    matches!(result, Err(WalletLibError::InvalidTransaction { .. }))
+}
+
+#[test]
+fn it_is_signed_by_the_user_and_the_sponsor() {
+    let transaction = sponsored(1000);
+    assert_eq!(transaction_signers(&transaction), vec![RistrettoPublicKey::from_secret_key(
+        &user_key()
+    )]);
+
+    // the partially signed transaction is handed over as JSON
+    let json = serde_json::to_string(&transaction).unwrap();
+    let transaction: Transaction = serde_json::from_str(&json).unwrap();
+
+    let transaction = sponsor_transaction(transaction, &sponsor_key(), &max_fee(1000)).unwrap();
+    assert_eq!(transaction_signers(&transaction), vec![
+        RistrettoPublicKey::from_secret_key(&user_key()),
+        RistrettoPublicKey::from_secret_key(&sponsor_key()),
+    ]);
+}
+
+#[test]
+fn the_sponsor_does_not_sign_twice() {
+    let transaction = sponsor_transaction(sponsored(1000), &sponsor_key(), &SponsorPolicy::default()).unwrap();
+    assert!(is_invalid_transaction(sponsor_transaction(
+        transaction,
+        &sponsor_key(),
+        &SponsorPolicy::default()
+    )));
+}
+
+#[test]
+fn the_sponsor_rejects_fees_over_the_maximum() {
+    assert!(is_invalid_transaction(sponsor_transaction(
+        sponsored(1001),
+        &sponsor_key(),
+        &max_fee(1000)
+    )));
+}
+
+#[test]
+fn the_sponsor_rejects_transactions_that_use_their_account() {
+    let sponsor_account = account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&sponsor_key()));
+    let withdraw_from_sponsor = Instruction::CallMethod {
+        component_address: sponsor_account,
+        method: "withdraw".to_string(),
+        args: args![Amount::new(10)],
+    };
+    let pay_fee = Instruction::CallMethod {
+        component_address: sponsor_account,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(10)],
+    };
+
+    // a transaction built without `build_sponsored_transaction`, that takes funds from the sponsor
+    let transaction = Transaction::builder()
+        .with_fee_instructions(vec![pay_fee.clone()])
+        .with_instructions(vec![withdraw_from_sponsor.clone()])
+        .sign(&user_key())
+        .build();
+    assert!(is_invalid_transaction(sponsor_transaction(transaction, &sponsor_key(), &SponsorPolicy::default())));
+
+    let transaction = Transaction::builder()
+        .with_fee_instructions(vec![pay_fee, withdraw_from_sponsor])
+        .with_instructions(create_user_account())
+        .sign(&user_key())
+        .build();
+    assert!(is_invalid_transaction(sponsor_transaction(transaction, &sponsor_key(), &SponsorPolicy::default())));
+}
+
+#[test]
+fn the_sponsor_rejects_fees_that_are_not_positive() {
+    let sponsor_account = account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&sponsor_key()));
+    let pay_fee = |amount: i64| Instruction::CallMethod {
+        component_address: sponsor_account,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(amount)],
+    };
+
+    // the negative fee brings the total under the maximum, while the sponsor would pay the large one
+    let transaction = Transaction::builder()
+        .with_fee_instructions(vec![pay_fee(1_000_000), pay_fee(-999_500)])
+        .with_instructions(create_user_account())
+        .sign(&user_key())
+        .build();
+    assert!(is_invalid_transaction(sponsor_transaction(transaction, &sponsor_key(), &max_fee(1000))));
+
+    let transaction = Transaction::builder()
+        .with_fee_instructions(vec![pay_fee(0)])
+        .with_instructions(create_user_account())
+        .sign(&user_key())
+        .build();
+    assert!(is_invalid_transaction(sponsor_transaction(transaction, &sponsor_key(), &SponsorPolicy::default())));
+}
+
+#[test]
+fn the_user_signs_first() {
+    let transaction = Transaction::builder()
+        .with_fee_instructions(vec![])
+        .with_instructions(create_user_account())
+        .build();
+    assert!(is_invalid_transaction(sponsor_transaction(transaction, &sponsor_key(), &SponsorPolicy::default())));
+}
+
+#[test]
+fn the_sponsor_checks_every_signature() {
+    // the signature of the user over another fee
+    let mut transaction = serde_json::to_value(sponsored(1000)).unwrap();
+    transaction["signatures"] = serde_json::to_value(sponsored(999)).unwrap()["signatures"].clone();
+    let transaction: Transaction = serde_json::from_value(transaction).unwrap();
+
+    assert!(is_invalid_transaction(sponsor_transaction(
+        transaction,
+        &sponsor_key(),
+        &SponsorPolicy::default()
+    )));
+}
+
+#[test]
+fn the_sponsor_only_allows_the_user_account_and_the_allowlist() {
+    let other_component =
+        account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(7u64)));
+    let call = |component_address| Instruction::CallMethod {
+        component_address,
+        method: "withdraw".to_string(),
+        args: args![Amount::new(10)],
+    };
+    let call_function = Instruction::CallFunction {
+        template_address: TemplateAddress::from([1u8; 32]),
+        function: "new".to_string(),
+        args: vec![],
+    };
+
+    let user_call = sponsored_instructions(1000, vec![call(user_account())]);
+    assert!(sponsor_transaction(user_call, &sponsor_key(), &SponsorPolicy::default()).is_ok());
+
+    let other_call = sponsored_instructions(1000, vec![call(other_component)]);
+    assert!(is_invalid_transaction(sponsor_transaction(
+        other_call.clone(),
+        &sponsor_key(),
+        &SponsorPolicy::default()
+    )));
+    let allowed_components = SponsorPolicy {
+        allowed_components: vec![other_component],
+        ..Default::default()
+    };
+    assert!(sponsor_transaction(other_call, &sponsor_key(), &allowed_components).is_ok());
+
+    let function_call = sponsored_instructions(1000, vec![call_function]);
+    assert!(is_invalid_transaction(sponsor_transaction(
+        function_call.clone(),
+        &sponsor_key(),
+        &SponsorPolicy::default()
+    )));
+    let allowed_templates = SponsorPolicy {
+        allowed_templates: vec![TemplateAddress::from([1u8; 32])],
+        ..Default::default()
+    };
+    assert!(sponsor_transaction(function_call, &sponsor_key(), &allowed_templates).is_ok());
+}
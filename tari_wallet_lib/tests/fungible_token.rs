@@ -0,0 +1,159 @@
+mod common;
+
+use std::str::FromStr;
+
+use tari_crypto::tari_utilities::hex::{to_hex, Hex};
+use tari_engine_types::commit_result::{RejectReason, TransactionResult};
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::resource::Resource;
+use tari_engine_types::substate::{Substate, SubstateDiff, SubstateId};
+use tari_template_lib::args::Arg;
+use tari_template_lib::auth::{OwnerRule, ResourceAccessRules};
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{ComponentAddress, Metadata, ResourceAddress, ResourceType};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::fungible_token::{
+    created_resource_address, FungibleToken, FungibleTokenParams, CREATE_TOKEN_FUNCTION,
+};
+use tari_wallet_lib::metadata::MetadataField;
+
+use common::{finalize_result, public_key, OTHER_PUBLIC_KEY as VIEW_KEY, PUBLIC_KEY as OWNER_PUBLIC_KEY};
+
+const TEMPLATE: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+fn account_address() -> ComponentAddress {
+    account_address_from_public_key(&public_key(OWNER_PUBLIC_KEY))
+}
+
+fn params() -> FungibleTokenParams {
+    FungibleTokenParams {
+        symbol: "TKN".to_string(),
+        divisibility: 6,
+        initial_supply: 1_000_000,
+        metadata: vec![MetadataField {
+            key: "name".to_string(),
+            value: "Token".to_string(),
+        }],
+        owner_rule: OwnerRule::OwnedBySigner,
+        confidential: false,
+        view_key: None,
+    }
+}
+
+fn resource_address(byte: u8) -> ResourceAddress {
+    ResourceAddress::from_str(&format!("resource_{}", to_hex(&[byte; 32]))).unwrap()
+}
+
+fn resource(resource_type: ResourceType) -> Substate {
+    let resource = Resource::new(
+        resource_type,
+        None,
+        OwnerRule::None,
+        ResourceAccessRules::new(),
+        Metadata::new(),
+        None,
+    );
+    Substate::new(0, resource)
+}
+
+#[test]
+fn it_creates_the_resource_and_deposits_the_initial_supply() {
+    let template_address = TemplateAddress::from_hex(TEMPLATE).unwrap();
+    let instructions = FungibleToken::new(params())
+        .unwrap()
+        .instructions(template_address, account_address())
+        .unwrap();
+
+    assert_eq!(instructions.len(), 3);
+    match &instructions[0] {
+        Instruction::CallFunction {
+            template_address: address,
+            function,
+            args,
+        } => {
+            assert_eq!(*address, template_address);
+            assert_eq!(function, CREATE_TOKEN_FUNCTION);
+            assert_eq!(args.len(), 7);
+            let mut expected = Metadata::new();
+            expected.insert("name".to_string(), "Token".to_string());
+            assert_eq!(args[3], Arg::Literal(tari_bor::encode(&expected).unwrap()));
+        },
+        instruction => panic!("unexpected instruction {:?}", instruction),
+    }
+    assert!(matches!(
+        &instructions[2],
+        Instruction::CallMethod { component_address, method, .. }
+            if *component_address == account_address() && method == "deposit"
+    ));
+}
+
+#[test]
+fn it_reads_defaults_from_json() {
+    let params: FungibleTokenParams =
+        serde_json::from_str(r#"{ "symbol": "TKN", "divisibility": 2, "initial_supply": 100 }"#).unwrap();
+
+    assert!(params.metadata.is_empty());
+    assert!(matches!(params.owner_rule, OwnerRule::OwnedBySigner));
+    assert!(!params.confidential);
+    assert!(FungibleToken::new(params).is_ok());
+}
+
+#[test]
+fn only_confidential_resources_have_a_view_key() {
+    let mut params = params();
+    params.view_key = Some(VIEW_KEY.to_string());
+    assert_eq!(
+        FungibleToken::new(params.clone()).unwrap_err(),
+        WalletLibError::invalid_argument("view_key", "only confidential resources have a view key")
+    );
+
+    params.confidential = true;
+    assert!(FungibleToken::new(params).is_ok());
+}
+
+#[test]
+fn it_rejects_empty_symbols_and_negative_supplies() {
+    let mut empty_symbol = params();
+    empty_symbol.symbol = " ".to_string();
+    assert!(FungibleToken::new(empty_symbol).is_err());
+
+    let mut negative_supply = params();
+    negative_supply.initial_supply = -1;
+    assert!(FungibleToken::new(negative_supply).is_err());
+}
+
+#[test]
+fn it_reads_the_created_resource_from_the_up_substates() {
+    let mut diff = SubstateDiff::new();
+    // the fee resource existed before, it is both a down and an up substate when its supply changes
+    diff.down(SubstateId::Resource(resource_address(1)), 0);
+    diff.up(SubstateId::Resource(resource_address(1)), resource(ResourceType::Fungible));
+    // new resources that are not fungible are ignored
+    diff.up(SubstateId::Resource(resource_address(2)), resource(ResourceType::NonFungible));
+    diff.up(SubstateId::Resource(resource_address(3)), resource(ResourceType::Confidential));
+
+    let result = finalize_result(TransactionResult::Accept(diff));
+    assert_eq!(created_resource_address(&result).unwrap(), resource_address(3));
+}
+
+#[test]
+fn it_rejects_results_without_a_new_fungible_resource() {
+    let mut diff = SubstateDiff::new();
+    diff.down(SubstateId::Resource(resource_address(1)), 0);
+    diff.up(SubstateId::Resource(resource_address(1)), resource(ResourceType::Fungible));
+    diff.up(SubstateId::Resource(resource_address(2)), resource(ResourceType::NonFungible));
+    let accepted = finalize_result(TransactionResult::Accept(diff));
+    assert!(matches!(
+        created_resource_address(&accepted),
+        Err(WalletLibError::InvalidTransaction { .. })
+    ));
+
+    let rejected = finalize_result(TransactionResult::Reject(RejectReason::ExecutionFailure(
+        "unknown function".to_string(),
+    )));
+    assert!(matches!(
+        created_resource_address(&rejected),
+        Err(WalletLibError::InvalidTransaction { .. })
+    ));
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_wallet_lib::component::get_account_address_from_public_key;
+use tari_wallet_lib::key_derivation::{build_key_derivation_report, derive_ristretto_private_key, KeyDerivationVersion};
+
+// The expected keys were computed outside of this crate from the definition of each scheme:
+// v0 reduces the 64 ASCII bytes of the hex key modulo the group order, v1 reduces the Blake2b-512 hash of the raw key
+// bytes with the "com.tari.snap.key_derivation.v1.metamask_entropy" domain separation tag
+const ECDSA_KEY: &str = "0x15a6e74b6507f10defccd76a24ca16cff94a4986f61e969908ec05d32ed0164e";
+const V0_SECRET_KEY: &str = "608349fb7b64c5fd462693be8f9e656fd57ad9a74148180f5f767a8c67c8ef0a";
+const V0_PUBLIC_KEY: &str = "60bf6b6308510d3311003250360acebb27f95f25191c668b1116d1f4f2fa2965";
+const V1_SECRET_KEY: &str = "b1fda7883da5feb9bfdb28e836df946d78a73e02b258e5fcae44cd369ad58d0e";
+const V1_PUBLIC_KEY: &str = "ccf992a313a981a306d86d0c22cc8fac9e5e765ea9d991a7b9b7b22d6ef44f37";
+
+fn derived_keys(ecdsa_key: &str, version: KeyDerivationVersion) -> (String, String) {
+    let secret_key = derive_ristretto_private_key(ecdsa_key, version).unwrap();
+    let public_key = RistrettoPublicKey::from_secret_key(&secret_key);
+    (secret_key.to_hex(), public_key.to_hex())
+}
+
+fn account_address(public_key: &str) -> String {
+    get_account_address_from_public_key(public_key).unwrap().to_string()
+}
+
+#[test]
+fn v0_matches_the_pinned_vector() {
+    assert_eq!(
+        derived_keys(ECDSA_KEY, KeyDerivationVersion::V0),
+        (V0_SECRET_KEY.to_string(), V0_PUBLIC_KEY.to_string())
+    );
+}
+
+#[test]
+fn v1_matches_the_pinned_vector() {
+    assert_eq!(
+        derived_keys(ECDSA_KEY, KeyDerivationVersion::V1),
+        (V1_SECRET_KEY.to_string(), V1_PUBLIC_KEY.to_string())
+    );
+}
+
+#[test]
+fn the_hex_prefix_is_optional() {
+    let no_prefix = ECDSA_KEY.strip_prefix("0x").unwrap();
+    for version in KeyDerivationVersion::ALL {
+        assert_eq!(derived_keys(no_prefix, version), derived_keys(ECDSA_KEY, version));
+    }
+}
+
+#[test]
+fn v1_rejects_keys_of_the_wrong_length() {
+    assert!(derive_ristretto_private_key("0x1234", KeyDerivationVersion::V1).is_err());
+    assert!(derive_ristretto_private_key("not hex", KeyDerivationVersion::V1).is_err());
+}
+
+#[test]
+fn the_report_lists_the_account_of_every_version() {
+    let report = build_key_derivation_report(ECDSA_KEY, &HashMap::new()).unwrap();
+
+    let accounts: Vec<_> = report
+        .accounts
+        .iter()
+        .map(|a| (a.version, a.public_key.as_str(), a.account_address.clone(), a.balance))
+        .collect();
+    assert_eq!(accounts, vec![
+        (KeyDerivationVersion::V0, V0_PUBLIC_KEY, account_address(V0_PUBLIC_KEY), 0),
+        (KeyDerivationVersion::V1, V1_PUBLIC_KEY, account_address(V1_PUBLIC_KEY), 0),
+    ]);
+    // nothing to migrate, new users get the latest version
+    assert!(report.funded_versions.is_empty());
+    assert_eq!(report.recommended_version, KeyDerivationVersion::LATEST);
+}
+
+#[test]
+fn the_report_recommends_the_newest_funded_version() {
+    let v0_funded = HashMap::from([(account_address(V0_PUBLIC_KEY), 100)]);
+    let report = build_key_derivation_report(ECDSA_KEY, &v0_funded).unwrap();
+    assert_eq!(report.funded_versions, vec![KeyDerivationVersion::V0]);
+    assert_eq!(report.recommended_version, KeyDerivationVersion::V0);
+    assert_eq!(report.accounts[0].balance, 100);
+
+    let both_funded = HashMap::from([
+        (account_address(V0_PUBLIC_KEY), 100),
+        (account_address(V1_PUBLIC_KEY), 5),
+    ]);
+    let report = build_key_derivation_report(ECDSA_KEY, &both_funded).unwrap();
+    assert_eq!(report.funded_versions, vec![KeyDerivationVersion::V0, KeyDerivationVersion::V1]);
+    assert_eq!(report.recommended_version, KeyDerivationVersion::V1);
+}
@@ -0,0 +1,67 @@
+use tari_crypto::tari_utilities::hex::{to_hex, Hex};
+use tari_wallet_lib::key_manager::{KeyManager, TRANSACTION_BRANCH, VIEW_KEY_BRANCH};
+
+const ROOT_SECRET: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+// The expected keys were computed with a separate Python implementation of the derivation of the daemon key manager
+// (hashlib Blake2b-512 and ristretto255 as specified in RFC 9496), not with a running daemon: the hash of the root
+// secret, the branch and the little endian index with the "com.tari.base_layer.key_manager" domain separation tag and
+// the "derive_key" label, reduced modulo the group order.
+// (branch, index, private key, public key)
+const VECTORS: &[(&str, u64, &str, &str)] = &[
+    (
+        TRANSACTION_BRANCH,
+        0,
+        "bd7e71ef67228d750e165f44d4a0dbdaf2e5c27881dcc6714bdd6c5e3a67d402",
+        "38c0ecd5009b1a09becbaf71d03cdb5cfbe9435b8b69a1672b3bb7ed6060673a",
+    ),
+    (
+        TRANSACTION_BRANCH,
+        1,
+        "bc49928fc00c044aa688a96c3da586081d984d23b3e0f459956b52b1ae1d5902",
+        "d6b3ddc2d8edbaa4fefe9832c2849fcb126b1d7e322f60b1705eb70c07e0bf65",
+    ),
+    (
+        TRANSACTION_BRANCH,
+        2,
+        "b5e4c91afb9ad08241c4ed52cb2cff1edd4e5d727bd4ac1928967e180397eb09",
+        "e6a37ed583964a20bbee5310b1c83ed7a06d5d91f2e4eea73f4fe9db73761c49",
+    ),
+    (
+        VIEW_KEY_BRANCH,
+        0,
+        "8569156e65b76e9c3dccf64d5b9dffa831f1436e5b03073915a680a15e3a9904",
+        "6e29fee6082e238475d40c6d4bed1d265ed5cd9d735a84a0380c09ff2d2e8d0d",
+    ),
+];
+
+#[test]
+fn it_derives_the_daemon_keys() {
+    let key_manager = KeyManager::new(&ROOT_SECRET);
+    for (branch, index, private_key, public_key) in VECTORS {
+        let derived_private_key = key_manager.derive_key(branch, *index).unwrap();
+        let derived_public_key = key_manager.derive_public_key(branch, *index).unwrap();
+        assert_eq!(derived_private_key.to_hex(), *private_key, "{} {}", branch, index);
+        assert_eq!(derived_public_key.to_hex(), *public_key, "{} {}", branch, index);
+    }
+}
+
+#[test]
+fn it_exports_the_daemon_public_keys() {
+    let key_manager = KeyManager::new(&ROOT_SECRET);
+    for (branch, index, _, public_key) in VECTORS {
+        assert_eq!(key_manager.get_public_key(branch, *index).unwrap(), *public_key, "{} {}", branch, index);
+    }
+
+    let from_hex = KeyManager::from_root_secret_hex(&to_hex(&ROOT_SECRET)).unwrap();
+    assert_eq!(from_hex.get_public_key(TRANSACTION_BRANCH, 0).unwrap(), VECTORS[0].3);
+}
+
+#[test]
+fn it_keeps_branches_and_indexes_apart() {
+    let key_manager = KeyManager::new(&ROOT_SECRET);
+    let account_key = key_manager.derive_key(TRANSACTION_BRANCH, 0).unwrap();
+    assert_ne!(account_key, key_manager.derive_key(VIEW_KEY_BRANCH, 0).unwrap());
+    assert_ne!(account_key, key_manager.derive_key(TRANSACTION_BRANCH, 1).unwrap());
+    assert_ne!(account_key, KeyManager::new(&[0u8; 16]).derive_key(TRANSACTION_BRANCH, 0).unwrap());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use tari_wallet_lib::key_derivation::KeyDerivationVersion;
+use tari_wallet_lib::keystore::{Keystore, KeystoreDerivation, KeystoreError};
+
+use common::secret_key;
+
+const PASSWORD: &str = "correct horse battery staple";
+
+fn keystore() -> Keystore {
+    let derivation = KeystoreDerivation::MetaMask {
+        version: KeyDerivationVersion::V1,
+    };
+    Keystore::encrypt(&secret_key(), PASSWORD, derivation).unwrap()
+}
+
+#[test]
+fn it_round_trips_a_key() {
+    let json = keystore().to_json().unwrap();
+    let keystore = Keystore::from_json(&json).unwrap();
+
+    assert_eq!(keystore.decrypt(PASSWORD).unwrap(), secret_key());
+    assert_eq!(keystore.derivation, KeystoreDerivation::MetaMask {
+        version: KeyDerivationVersion::V1
+    });
+}
+
+#[test]
+fn it_rejects_a_wrong_password() {
+    assert_eq!(
+        keystore().decrypt("wrong password"),
+        Err(KeystoreError::DecryptionFailed)
+    );
+}
+
+#[test]
+fn it_rejects_a_modified_keystore() {
+    let mut keystore = keystore();
+    keystore.derivation = KeystoreDerivation::KeyManager {
+        branch: "transaction".to_string(),
+        index: 1,
+    };
+    assert_eq!(keystore.decrypt(PASSWORD), Err(KeystoreError::DecryptionFailed));
+
+    let mut keystore = self::keystore();
+    let mut ciphertext = keystore.ciphertext.into_bytes();
+    ciphertext[0] = if ciphertext[0] == b'0' { b'1' } else { b'0' };
+    keystore.ciphertext = String::from_utf8(ciphertext).unwrap();
+    assert_eq!(keystore.decrypt(PASSWORD), Err(KeystoreError::DecryptionFailed));
+}
+
+#[test]
+fn it_rejects_unsupported_versions() {
+    let mut keystore = keystore();
+    keystore.version = 2;
+    assert_eq!(keystore.decrypt(PASSWORD), Err(KeystoreError::UnsupportedVersion(2)));
+}
@@ -0,0 +1,120 @@
+use tari_common_types::types::Commitment;
+use tari_crypto::keys::{PublicKey, SecretKey};
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_engine_types::confidential::ElgamalVerifiableBalance;
+use tari_wallet_lib::crypto::{BabyStepGiantStepTable, LookupTableError};
+
+const TABLE_SIZE: u64 = 16;
+
+fn value_point(value: u64) -> RistrettoPublicKey {
+    RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(value))
+}
+
+fn is_invalid_encoding(result: Result<BabyStepGiantStepTable, LookupTableError>) -> bool {
+    matches!(result, Err(LookupTableError::InvalidEncoding(_)))
+}
+
+#[test]
+fn it_round_trips_through_bytes() {
+    let table = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap();
+    let bytes = table.to_bytes();
+    let decoded = BabyStepGiantStepTable::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decoded.size(), TABLE_SIZE);
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.find_value(&value_point(100), 0..=1000), Some(100));
+}
+
+#[test]
+fn it_rejects_invalid_sizes() {
+    assert_eq!(
+        BabyStepGiantStepTable::build(0).err(),
+        Some(LookupTableError::InvalidSize(0))
+    );
+
+    // the size in the header is zero
+    let mut bytes = BabyStepGiantStepTable::build(1).unwrap().to_bytes();
+    bytes.truncate(13);
+    bytes[5..13].copy_from_slice(&0u64.to_le_bytes());
+    assert_eq!(
+        BabyStepGiantStepTable::from_bytes(&bytes).err(),
+        Some(LookupTableError::InvalidSize(0))
+    );
+}
+
+#[test]
+fn it_rejects_a_wrong_magic_or_version() {
+    let bytes = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap().to_bytes();
+
+    let mut wrong_magic = bytes.clone();
+    wrong_magic[0] ^= 0xff;
+    assert!(is_invalid_encoding(BabyStepGiantStepTable::from_bytes(&wrong_magic)));
+
+    let mut wrong_version = bytes;
+    wrong_version[4] += 1;
+    assert!(is_invalid_encoding(BabyStepGiantStepTable::from_bytes(&wrong_version)));
+}
+
+#[test]
+fn it_rejects_truncated_input() {
+    let bytes = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap().to_bytes();
+
+    assert!(is_invalid_encoding(BabyStepGiantStepTable::from_bytes(&[])));
+    assert!(is_invalid_encoding(BabyStepGiantStepTable::from_bytes(&bytes[..10])));
+    assert!(is_invalid_encoding(BabyStepGiantStepTable::from_bytes(
+        &bytes[..bytes.len() - 1]
+    )));
+}
+
+#[test]
+fn it_finds_every_value_of_the_range() {
+    let table = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap();
+
+    // values within the baby steps, on giant step boundaries and in the last giant step
+    for value in 0..=5 * TABLE_SIZE + 3 {
+        assert_eq!(table.find_value(&value_point(value), 0..=5 * TABLE_SIZE + 3), Some(value));
+    }
+    // ranges that do not start at zero
+    assert_eq!(table.find_value(&value_point(60), 50..=70), Some(60));
+    assert_eq!(table.find_value(&value_point(1_000_000), 999_990..=1_000_010), Some(1_000_000));
+}
+
+#[test]
+fn it_searches_the_whole_u64_range() {
+    // with a single baby step the number of giant steps over the whole range does not fit in a u64
+    let table = BabyStepGiantStepTable::build(1).unwrap();
+    assert_eq!(table.find_value(&value_point(5), 0..=u64::MAX), Some(5));
+    assert_eq!(table.find_value(&value_point(u64::MAX), u64::MAX - 3..=u64::MAX), Some(u64::MAX));
+
+    let table = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap();
+    assert_eq!(table.find_value(&value_point(100), 0..=u64::MAX), Some(100));
+    assert_eq!(table.find_value(&value_point(u64::MAX), u64::MAX - 100..=u64::MAX), Some(u64::MAX));
+}
+
+#[test]
+fn it_misses_values_outside_of_the_range() {
+    let table = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap();
+
+    assert_eq!(table.find_value(&value_point(40), 50..=70), None);
+    assert_eq!(table.find_value(&value_point(71), 50..=70), None);
+    assert_eq!(table.find_value(&value_point(60), 70..=50), None);
+}
+
+#[test]
+fn it_decrypts_viewable_balances() {
+    let table = BabyStepGiantStepTable::build(TABLE_SIZE).unwrap();
+    let (view_key, public_view_key) = RistrettoPublicKey::random_keypair(&mut rand::thread_rng());
+
+    for value in [0, 1, TABLE_SIZE, 123, 999] {
+        // E = v·G + r·K, R = r·G
+        let nonce = RistrettoSecretKey::random(&mut rand::thread_rng());
+        let encrypted = &value_point(value) + &(&nonce * &public_view_key);
+        let balance = ElgamalVerifiableBalance {
+            encrypted: Commitment::from_public_key(&encrypted),
+            public_nonce: RistrettoPublicKey::from_secret_key(&nonce),
+        };
+
+        assert_eq!(table.decrypt_balance(&view_key, &balance, 0..=1000), Some(value));
+        assert_eq!(table.decrypt_balance(&view_key, &balance, value + 1..=1000), None);
+    }
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_wallet_lib::message_signing::{
+    sign_message,
+    signature_from_hex,
+    signature_to_hex,
+    verify_message_signature,
+    MessageSignatureError,
+};
+
+use common::{secret_key, OTHER_PUBLIC_KEY, PUBLIC_KEY};
+
+const MESSAGE: &[u8] = b"Sign in to example.com";
+const DOMAIN: &str = "example.com";
+const SIGNATURE: &str = "6e29fee6082e238475d40c6d4bed1d265ed5cd9d735a84a0380c09ff2d2e8d0dbb08a21fa902b0b090d7cf51834d8525e7e6c22196f6a68d0263132203b61b0b";
+
+fn public_key() -> RistrettoPublicKey {
+    RistrettoPublicKey::from_hex(PUBLIC_KEY).unwrap()
+}
+
+#[test]
+fn it_round_trips_a_signature() {
+    let signature = sign_message(&secret_key(), MESSAGE, DOMAIN).unwrap();
+    let signature = signature_from_hex(&signature_to_hex(&signature)).unwrap();
+    assert_eq!(RistrettoPublicKey::from_secret_key(&secret_key()), public_key());
+    assert!(verify_message_signature(&public_key(), MESSAGE, &signature, DOMAIN).unwrap());
+}
+
+// the signature is built with a fixed nonce by the unit test of `message_signing`
+#[test]
+fn it_verifies_the_known_vector() {
+    let signature = signature_from_hex(SIGNATURE).unwrap();
+    assert!(verify_message_signature(&public_key(), MESSAGE, &signature, DOMAIN).unwrap());
+}
+
+#[test]
+fn it_rejects_other_domains_messages_and_keys() {
+    let signature = signature_from_hex(SIGNATURE).unwrap();
+    let other_public_key = RistrettoPublicKey::from_hex(OTHER_PUBLIC_KEY).unwrap();
+
+    assert!(!verify_message_signature(&public_key(), MESSAGE, &signature, "evil.com").unwrap());
+    assert!(!verify_message_signature(&public_key(), b"Sign in to evil.com", &signature, DOMAIN).unwrap());
+    assert!(!verify_message_signature(&other_public_key, MESSAGE, &signature, DOMAIN).unwrap());
+}
+
+#[test]
+fn it_requires_a_domain() {
+    assert_eq!(
+        sign_message(&secret_key(), MESSAGE, " ").unwrap_err(),
+        MessageSignatureError::MissingDomain
+    );
+    let signature = signature_from_hex(SIGNATURE).unwrap();
+    assert_eq!(
+        verify_message_signature(&public_key(), MESSAGE, &signature, "").unwrap_err(),
+        MessageSignatureError::MissingDomain
+    );
+}
+
+#[test]
+fn it_rejects_domains_with_surrounding_whitespace() {
+    assert!(matches!(
+        sign_message(&secret_key(), MESSAGE, " example.com"),
+        Err(MessageSignatureError::InvalidDomain(_))
+    ));
+    let signature = signature_from_hex(SIGNATURE).unwrap();
+    assert!(matches!(
+        verify_message_signature(&public_key(), MESSAGE, &signature, "example.com\n"),
+        Err(MessageSignatureError::InvalidDomain(_))
+    ));
+}
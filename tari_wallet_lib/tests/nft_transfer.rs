@@ -0,0 +1,76 @@
+mod common;
+
+use std::str::FromStr;
+
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::prelude::{ComponentAddress, NonFungibleAddress, NonFungibleId, ResourceAddress};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::nft_transfer::NftTransfer;
+
+use common::{
+    public_key, OTHER_PUBLIC_KEY as DESTINATION_PUBLIC_KEY, PUBLIC_KEY as SOURCE_PUBLIC_KEY, RESOURCE,
+};
+
+fn source_account() -> ComponentAddress {
+    account_address_from_public_key(&public_key(SOURCE_PUBLIC_KEY))
+}
+
+fn transfer(create_destination_account: bool, ids: Vec<NonFungibleId>) -> Result<NftTransfer, WalletLibError> {
+    NftTransfer::new(
+        source_account(),
+        public_key(DESTINATION_PUBLIC_KEY),
+        create_destination_account,
+        ResourceAddress::from_str(RESOURCE).unwrap(),
+        ids,
+    )
+}
+
+#[test]
+fn it_withdraws_and_deposits_each_token() {
+    let transfer = transfer(true, vec![NonFungibleId::from_u64(1), NonFungibleId::from_u64(2)]).unwrap();
+    let instructions = transfer.instructions();
+
+    assert_eq!(instructions.len(), 7);
+    assert!(matches!(instructions[0], Instruction::CreateAccount { .. }));
+    let withdrawals = instructions
+        .iter()
+        .filter(|i| matches!(i, Instruction::CallMethod { method, .. } if method == "withdraw_non_fungible"))
+        .count();
+    assert_eq!(withdrawals, 2);
+
+    let destination = account_address_from_public_key(&public_key(DESTINATION_PUBLIC_KEY));
+    match &instructions[3] {
+        Instruction::CallMethod {
+            component_address,
+            method,
+            ..
+        } => {
+            assert_eq!(*component_address, destination);
+            assert_eq!(method, "deposit");
+        },
+        instruction => panic!("unexpected instruction {:?}", instruction),
+    }
+}
+
+#[test]
+fn it_requires_the_resource_and_the_tokens() {
+    let resource = ResourceAddress::from_str(RESOURCE).unwrap();
+    let transfer = transfer(false, vec![NonFungibleId::from_u64(1)]).unwrap();
+    let inputs: Vec<SubstateId> = transfer.inputs().iter().map(|i| i.substate_id().clone()).collect();
+
+    assert_eq!(inputs, vec![
+        SubstateId::Resource(resource),
+        SubstateId::NonFungible(NonFungibleAddress::new(resource, NonFungibleId::from_u64(1))),
+    ]);
+}
+
+#[test]
+fn it_rejects_empty_and_repeated_ids() {
+    assert!(transfer(false, vec![]).is_err());
+    assert_eq!(
+        transfer(false, vec![NonFungibleId::from_u64(1), NonFungibleId::from_u64(1)]).unwrap_err(),
+        WalletLibError::invalid_argument("ids[1]", "the id u64:1 is repeated")
+    );
+}
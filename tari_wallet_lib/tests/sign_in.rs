@@ -0,0 +1,94 @@
+mod common;
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::sign_in::{SignInError, SignInMessage};
+
+use common::{secret_key, PUBLIC_KEY};
+
+const DOMAIN: &str = "example.com";
+const NONCE: &str = "a1b2c3d4e5f60718";
+const ISSUED_AT: u64 = 1_700_000_000;
+const EXPIRATION_TIME: u64 = ISSUED_AT + 300;
+
+fn message() -> SignInMessage {
+    SignInMessage::builder()
+        .with_domain(DOMAIN)
+        .with_statement("Sign in to the example dApp")
+        .with_secret_key(&secret_key())
+        .with_nonce(NONCE)
+        .with_issued_at(ISSUED_AT)
+        .with_expiration_time(EXPIRATION_TIME)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn it_serializes_a_canonical_message() {
+    let message = message();
+    let account_address = account_address_from_public_key(&message.public_key);
+
+    let expected = format!(
+        "example.com wants you to sign in with your Tari account:\n{}\n\nSign in to the example dApp\n\nPublic Key: \
+         {}\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+        account_address, PUBLIC_KEY, NONCE, ISSUED_AT, EXPIRATION_TIME
+    );
+    assert_eq!(message.to_string(), expected);
+    assert_eq!(SignInMessage::from_str(&expected).unwrap(), message);
+}
+
+#[test]
+fn it_rejects_non_canonical_messages() {
+    let text = message().to_string().replace("Nonce: ", "Nonce:  ");
+    assert!(matches!(
+        SignInMessage::from_str(&text),
+        Err(SignInError::InvalidMessage(_))
+    ));
+}
+
+#[test]
+fn it_verifies_a_signed_message_once() {
+    let message = message();
+    let signature = message.sign(&secret_key()).unwrap();
+    let mut used_nonces = HashSet::new();
+
+    message
+        .verify(&signature, DOMAIN, ISSUED_AT + 10, &mut used_nonces)
+        .unwrap();
+    assert_eq!(
+        message.verify(&signature, DOMAIN, ISSUED_AT + 20, &mut used_nonces),
+        Err(SignInError::NonceReused(NONCE.to_string()))
+    );
+}
+
+#[test]
+fn it_rejects_expired_and_wrong_domain_messages() {
+    let message = message();
+    let signature = message.sign(&secret_key()).unwrap();
+    let mut used_nonces = HashSet::new();
+
+    assert!(matches!(
+        message.verify(&signature, DOMAIN, EXPIRATION_TIME, &mut used_nonces),
+        Err(SignInError::Expired { .. })
+    ));
+    assert!(matches!(
+        message.verify(&signature, "evil.com", ISSUED_AT + 10, &mut used_nonces),
+        Err(SignInError::DomainMismatch { .. })
+    ));
+    // Rejected messages do not burn the nonce
+    assert!(used_nonces.is_empty());
+}
+
+#[test]
+fn it_rejects_a_tampered_message() {
+    let signature = message().sign(&secret_key()).unwrap();
+    let mut tampered = message();
+    tampered.expiration_time += 3600;
+
+    assert_eq!(
+        tampered.verify(&signature, DOMAIN, ISSUED_AT + 10, &mut HashSet::new()),
+        Err(SignInError::InvalidSignature)
+    );
+}
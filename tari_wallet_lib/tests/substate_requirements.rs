@@ -0,0 +1,135 @@
+mod common;
+
+use std::str::FromStr;
+
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_crypto::tari_utilities::ByteArray;
+use tari_engine_types::instruction::Instruction;
+use tari_engine_types::substate::SubstateId;
+use tari_template_lib::args;
+use tari_template_lib::models::TemplateAddress;
+use tari_template_lib::prelude::{
+    Amount, NonFungibleAddress, NonFungibleId, ResourceAddress, RistrettoPublicKeyBytes,
+};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::substate_requirements::infer_substate_requirements;
+
+use common::{
+    public_key, OTHER_PUBLIC_KEY as RECIPIENT_PUBLIC_KEY, PUBLIC_KEY as SENDER_PUBLIC_KEY, RESOURCE,
+};
+
+fn transfer_instructions(create_recipient: bool) -> (Vec<Instruction>, Vec<Instruction>) {
+    let sender = account_address_from_public_key(&public_key(SENDER_PUBLIC_KEY));
+    let recipient = account_address_from_public_key(&public_key(RECIPIENT_PUBLIC_KEY));
+    let resource = ResourceAddress::from_str(RESOURCE).unwrap();
+
+    let fee_instructions = vec![Instruction::CallMethod {
+        component_address: sender,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(500)],
+    }];
+    let mut instructions = vec![];
+    if create_recipient {
+        instructions.push(Instruction::CreateAccount {
+            owner_public_key: public_key(RECIPIENT_PUBLIC_KEY),
+            workspace_bucket: None,
+        });
+    }
+    instructions.extend(vec![
+        Instruction::CallMethod {
+            component_address: sender,
+            method: "withdraw".to_string(),
+            args: args![resource, Amount::new(100)],
+        },
+        Instruction::PutLastInstructionOutputOnWorkspace {
+            key: b"bucket".to_vec(),
+        },
+        Instruction::CallMethod {
+            component_address: recipient,
+            method: "deposit".to_string(),
+            args: args![Workspace("bucket")],
+        },
+    ]);
+    (fee_instructions, instructions)
+}
+
+fn substate_ids(fee_instructions: &[Instruction], instructions: &[Instruction]) -> Vec<SubstateId> {
+    infer_substate_requirements(fee_instructions, instructions, &[])
+        .into_iter()
+        .map(|r| r.substate_id().clone())
+        .collect()
+}
+
+#[test]
+fn it_infers_the_substates_of_a_transfer() {
+    let (fee_instructions, instructions) = transfer_instructions(false);
+
+    assert_eq!(substate_ids(&fee_instructions, &instructions), vec![
+        SubstateId::Component(account_address_from_public_key(&public_key(SENDER_PUBLIC_KEY))),
+        SubstateId::Resource(ResourceAddress::from_str(RESOURCE).unwrap()),
+        SubstateId::Component(account_address_from_public_key(&public_key(RECIPIENT_PUBLIC_KEY))),
+    ]);
+}
+
+#[test]
+fn it_excludes_accounts_created_by_the_transaction() {
+    let (fee_instructions, instructions) = transfer_instructions(true);
+
+    assert_eq!(substate_ids(&fee_instructions, &instructions), vec![
+        SubstateId::Component(account_address_from_public_key(&public_key(SENDER_PUBLIC_KEY))),
+        SubstateId::Resource(ResourceAddress::from_str(RESOURCE).unwrap()),
+    ]);
+}
+
+#[test]
+fn it_excludes_components_created_by_the_transaction() {
+    let (fee_instructions, mut instructions) = transfer_instructions(false);
+    let sender = account_address_from_public_key(&public_key(SENDER_PUBLIC_KEY));
+    // the recipient stands in for a component created by a template function earlier in the transaction
+    let created = account_address_from_public_key(&public_key(RECIPIENT_PUBLIC_KEY));
+    instructions.insert(0, Instruction::CallFunction {
+        template_address: TemplateAddress::from([1u8; 32]),
+        function: "create".to_string(),
+        args: vec![],
+    });
+
+    let substate_ids: Vec<SubstateId> = infer_substate_requirements(&fee_instructions, &instructions, &[created])
+        .into_iter()
+        .map(|r| r.substate_id().clone())
+        .collect();
+    assert_eq!(substate_ids, vec![
+        SubstateId::Component(sender),
+        SubstateId::Resource(ResourceAddress::from_str(RESOURCE).unwrap()),
+    ]);
+}
+
+#[test]
+fn it_includes_badges_passed_as_arguments() {
+    let resource = ResourceAddress::from_str(RESOURCE).unwrap();
+    let badge = NonFungibleAddress::new(resource, NonFungibleId::from_u64(1));
+    let instructions = vec![Instruction::CallFunction {
+        template_address: TemplateAddress::from([1u8; 32]),
+        function: "new".to_string(),
+        args: args![badge.clone()],
+    }];
+
+    assert_eq!(substate_ids(&[], &instructions), vec![
+        SubstateId::Resource(resource),
+        SubstateId::NonFungible(badge),
+    ]);
+}
+
+#[test]
+fn it_does_not_include_public_key_badges() {
+    let public_key_bytes = RistrettoPublicKeyBytes::from_bytes(public_key(SENDER_PUBLIC_KEY).as_bytes()).unwrap();
+    let owner_token = NonFungibleAddress::from_public_key(public_key_bytes);
+    let instructions = vec![Instruction::CallFunction {
+        template_address: TemplateAddress::from([1u8; 32]),
+        function: "create".to_string(),
+        args: args![owner_token.clone()],
+    }];
+
+    assert_eq!(substate_ids(&[], &instructions), vec![SubstateId::Resource(
+        *owner_token.resource_address()
+    )]);
+}
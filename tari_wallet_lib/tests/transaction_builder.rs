@@ -0,0 +1,115 @@
+mod common;
+
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::prelude::{Amount, ComponentAddress};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::error::WalletLibError;
+use tari_wallet_lib::transaction_builder::TransactionBuilder;
+
+use common::secret_key;
+
+fn account_address() -> ComponentAddress {
+    account_address_from_public_key(&RistrettoPublicKey::from_secret_key(&secret_key()))
+}
+
+fn deposit(bucket: &str) -> Instruction {
+    Instruction::CallMethod {
+        component_address: account_address(),
+        method: "deposit".to_string(),
+        args: args![Workspace(bucket)],
+    }
+}
+
+fn is_invalid_transaction<T>(result: Result<T, WalletLibError>) -> bool {
+    matches!(result, Err(WalletLibError::InvalidTransaction { .. }))
+}
+
+#[test]
+fn it_builds_a_signed_transaction() {
+    let mut builder = TransactionBuilder::new();
+    builder
+        .add_instruction(Instruction::CallMethod {
+            component_address: account_address(),
+            method: "withdraw".to_string(),
+            args: args![Amount::new(10)],
+        })
+        .unwrap();
+    builder.put_last_output_on_workspace("bucket").unwrap();
+    builder.add_instruction(deposit("bucket")).unwrap();
+    builder.pay_fee(&account_address().to_string(), 1000).unwrap();
+    builder.add_input(&account_address().to_string(), None).unwrap();
+    builder.sign_with(&secret_key()).unwrap();
+
+    let transaction = builder.build_transaction().unwrap();
+    assert_eq!(transaction.instructions().len(), 3);
+    assert_eq!(transaction.fee_instructions().len(), 1);
+    assert_eq!(transaction.signatures().len(), 1);
+}
+
+#[test]
+fn it_rejects_undefined_workspace_keys() {
+    let mut builder = TransactionBuilder::new();
+    assert!(is_invalid_transaction(builder.add_instruction(deposit("bucket"))));
+    assert!(is_invalid_transaction(builder.put_last_output_on_workspace("bucket")));
+}
+
+#[test]
+fn it_rejects_duplicated_inputs() {
+    let mut builder = TransactionBuilder::new();
+    builder.add_input(&account_address().to_string(), None).unwrap();
+    assert!(is_invalid_transaction(
+        builder.add_input(&account_address().to_string(), Some(1))
+    ));
+}
+
+#[test]
+fn it_requires_fees_and_a_signature() {
+    let mut builder = TransactionBuilder::new();
+    assert!(is_invalid_transaction(builder.sign_with(&secret_key())));
+    assert!(matches!(
+        builder.pay_fee(&account_address().to_string(), 0),
+        Err(WalletLibError::InvalidArgument { .. })
+    ));
+
+    builder.pay_fee(&account_address().to_string(), 1000).unwrap();
+    assert!(is_invalid_transaction(builder.build_transaction()));
+}
+
+#[test]
+fn it_cannot_be_modified_after_signing() {
+    let mut builder = TransactionBuilder::new();
+    builder.pay_fee(&account_address().to_string(), 1000).unwrap();
+    builder.sign_with(&secret_key()).unwrap();
+
+    assert!(is_invalid_transaction(
+        builder.pay_fee(&account_address().to_string(), 1000)
+    ));
+}
+
+#[test]
+fn it_validates_fee_instructions() {
+    let mut builder = TransactionBuilder::new();
+    assert!(matches!(
+        builder.add_fee_instruction(Instruction::CallMethod {
+            component_address: account_address(),
+            method: " ".to_string(),
+            args: vec![],
+        }),
+        Err(WalletLibError::InvalidArgument { .. })
+    ));
+    assert!(is_invalid_transaction(builder.add_fee_instruction(deposit("bucket"))));
+
+    // the workspace of the fee instructions is not shared with the other instructions
+    builder
+        .add_instruction(Instruction::CallMethod {
+            component_address: account_address(),
+            method: "withdraw".to_string(),
+            args: args![Amount::new(10)],
+        })
+        .unwrap();
+    builder.put_last_output_on_workspace("bucket").unwrap();
+    assert!(is_invalid_transaction(builder.add_fee_instruction(deposit("bucket"))));
+}
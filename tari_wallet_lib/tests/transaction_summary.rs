@@ -0,0 +1,98 @@
+mod common;
+
+use std::str::FromStr;
+
+use tari_engine_types::instruction::Instruction;
+use tari_template_lib::args;
+use tari_template_lib::prelude::{Amount, ResourceAddress};
+use tari_wallet_lib::component::account_address_from_public_key;
+use tari_wallet_lib::transaction_summary::{summarize_instructions, DecodedArg};
+
+use common::{public_key, OTHER_PUBLIC_KEY, PUBLIC_KEY as SIGNER_PUBLIC_KEY, RESOURCE};
+
+#[test]
+fn it_describes_a_transfer() {
+    let signer = public_key(SIGNER_PUBLIC_KEY);
+    let own_account = account_address_from_public_key(&signer);
+    let other_account = account_address_from_public_key(&public_key(OTHER_PUBLIC_KEY));
+    let resource = ResourceAddress::from_str(RESOURCE).unwrap();
+
+    let fee_instructions = vec![Instruction::CallMethod {
+        component_address: own_account,
+        method: "pay_fee".to_string(),
+        args: args![Amount::new(500)],
+    }];
+    let instructions = vec![
+        Instruction::CallMethod {
+            component_address: own_account,
+            method: "withdraw".to_string(),
+            args: args![resource, Amount::new(100)],
+        },
+        Instruction::PutLastInstructionOutputOnWorkspace {
+            key: b"bucket".to_vec(),
+        },
+        Instruction::CallMethod {
+            component_address: other_account,
+            method: "deposit".to_string(),
+            args: args![Workspace("bucket")],
+        },
+    ];
+
+    let summary = summarize_instructions(&fee_instructions, &instructions, Some(&signer));
+    let descriptions: Vec<String> = summary.steps.iter().map(|s| s.description.clone()).collect();
+    assert_eq!(descriptions, vec![
+        "Pay a fee of up to 500 from your account".to_string(),
+        format!("Withdraw 100 of {} from your account", RESOURCE),
+        "Keep the result of the previous step as bucket".to_string(),
+        format!("Deposit bucket into {}", other_account),
+    ]);
+
+    let touches_own_account: Vec<bool> = summary.steps.iter().map(|s| s.touches_own_account).collect();
+    assert_eq!(touches_own_account, vec![true, true, false, false]);
+    assert!(summary.steps[0].is_fee_instruction);
+    assert_eq!(summary.steps[1].args, vec![
+        DecodedArg::Address(RESOURCE.to_string()),
+        DecodedArg::Amount(100)
+    ]);
+}
+
+#[test]
+fn it_marks_the_creation_of_the_signer_account() {
+    let signer = public_key(SIGNER_PUBLIC_KEY);
+    let instructions = vec![
+        Instruction::CreateAccount {
+            owner_public_key: signer.clone(),
+            workspace_bucket: None,
+        },
+        Instruction::CreateAccount {
+            owner_public_key: public_key(OTHER_PUBLIC_KEY),
+            workspace_bucket: Some("bucket".to_string()),
+        },
+    ];
+
+    let summary = summarize_instructions(&[], &instructions, Some(&signer));
+    assert_eq!(summary.steps[0].description, "Create your account");
+    assert!(summary.steps[0].touches_own_account);
+    assert_eq!(
+        summary.steps[1].description,
+        format!("Create an account for {} and deposit bucket into it", OTHER_PUBLIC_KEY)
+    );
+    assert!(!summary.steps[1].touches_own_account);
+}
+
+#[test]
+fn it_only_labels_integers_as_amounts_for_methods_that_take_one() {
+    let component = account_address_from_public_key(&public_key(OTHER_PUBLIC_KEY));
+    let instructions = vec![Instruction::CallMethod {
+        component_address: component,
+        method: "set_level".to_string(),
+        args: args![7u32],
+    }];
+
+    let summary = summarize_instructions(&[], &instructions, None);
+    assert_eq!(summary.steps[0].args, vec![DecodedArg::Integer(7)]);
+    assert_eq!(
+        summary.steps[0].description,
+        format!("Call method set_level on {} with 7", component)
+    );
+}
@@ -0,0 +1,27 @@
+//! Template used by the snap to split a withdrawn bucket between several accounts, see
+//! `tari_wallet_lib::batch_transfer`. The account template has no method that splits a bucket.
+
+use tari_template_lib::prelude::*;
+
+#[template]
+mod bucket_split_template {
+    use super::*;
+
+    pub struct BucketSplit {}
+
+    impl BucketSplit {
+        /// Deposits each amount of `deposits` into its account. The amounts must add up to the amount of `bucket`.
+        pub fn split_and_deposit(mut bucket: Bucket, deposits: Vec<(ComponentAddress, Amount)>) {
+            let total = deposits.iter().fold(Amount::zero(), |total, (_, amount)| total + *amount);
+            assert_eq!(bucket.amount(), total, "the deposits must add up to the bucket amount");
+
+            // the last account takes what is left, so no empty bucket remains
+            let ((last_account, _), rest) = deposits.split_last().expect("there must be at least one deposit");
+            for (account, amount) in rest {
+                let part = bucket.take(*amount);
+                ComponentManager::get(*account).call::<_, ()>("deposit", args![part]);
+            }
+            ComponentManager::get(*last_account).call::<_, ()>("deposit", args![bucket]);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use tari_crypto::keys::PublicKey;
+use tari_crypto::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::prelude::{Amount, ComponentAddress, NonFungibleAddress};
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+use tari_wallet_lib::batch_transfer::{BatchTransfer, TransferItem};
+
+struct Account {
+    address: ComponentAddress,
+    owner_proof: NonFungibleAddress,
+    secret_key: RistrettoSecretKey,
+}
+
+impl Account {
+    fn new(test: &mut TemplateTest) -> Self {
+        let (address, owner_proof, secret_key) = test.create_funded_account();
+        Self {
+            address,
+            owner_proof,
+            secret_key,
+        }
+    }
+
+    fn public_key(&self) -> RistrettoPublicKey {
+        RistrettoPublicKey::from_secret_key(&self.secret_key)
+    }
+
+    fn balance(&self, test: &mut TemplateTest) -> Amount {
+        let transaction = Transaction::builder()
+            .call_method(self.address, "balance", args![XTR])
+            .sign(&self.secret_key)
+            .build();
+        let result = test.execute_expect_success(transaction, vec![self.owner_proof.clone()]);
+        result.finalize.execution_results[0].decode().unwrap()
+    }
+}
+
+fn item(recipient: &Account, amount: i64) -> TransferItem {
+    TransferItem {
+        destination_public_key: recipient.public_key().to_hex(),
+        resource_address: XTR.to_string(),
+        amount,
+        create_account: false,
+    }
+}
+
+// executes the instructions built by the wallet library, so their arguments must match the template ABI
+fn transfer(test: &mut TemplateTest, source: &Account, items: Vec<TransferItem>) -> Transaction {
+    let template_address = test.get_template_address("BucketSplit");
+    let batch = BatchTransfer::new(&source.public_key(), items).unwrap();
+    Transaction::builder()
+        .with_instructions(batch.instructions(source.address, template_address))
+        .sign(&source.secret_key)
+        .build()
+}
+
+#[test]
+fn it_splits_one_withdrawal_between_the_recipients() {
+    let mut test = TemplateTest::new(["."]);
+    let source = Account::new(&mut test);
+    let first = Account::new(&mut test);
+    let second = Account::new(&mut test);
+    let (source_balance, first_balance, second_balance) =
+        (source.balance(&mut test), first.balance(&mut test), second.balance(&mut test));
+
+    let transaction = transfer(&mut test, &source, vec![item(&first, 100), item(&second, 30), item(&first, 5)]);
+    test.execute_expect_success(transaction, vec![source.owner_proof.clone()]);
+
+    assert_eq!(source.balance(&mut test), source_balance - Amount::new(135));
+    assert_eq!(first.balance(&mut test), first_balance + Amount::new(105));
+    assert_eq!(second.balance(&mut test), second_balance + Amount::new(30));
+}
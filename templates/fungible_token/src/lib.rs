@@ -0,0 +1,50 @@
+//! Template used by the snap to create fungible resources, see `tari_wallet_lib::fungible_token`.
+//! The signature of `create_fungible_token` is the one the wallet library encodes its arguments for.
+
+use tari_template_lib::prelude::*;
+
+/// Metadata key of the number of decimal places used to display amounts of the resource
+pub const DIVISIBILITY_METADATA_KEY: &str = "divisibility";
+
+#[template]
+mod fungible_token_template {
+    use super::*;
+
+    pub struct FungibleToken {}
+
+    impl FungibleToken {
+        /// Creates a new resource and returns its initial supply, which is revealed for confidential resources
+        pub fn create_fungible_token(
+            symbol: String,
+            divisibility: u8,
+            initial_supply: Amount,
+            metadata: Metadata,
+            owner_rule: OwnerRule,
+            confidential: bool,
+            view_key: Option<RistrettoPublicKeyBytes>,
+        ) -> Bucket {
+            if confidential {
+                let mut builder = ResourceBuilder::confidential()
+                    .with_metadata(metadata)
+                    .with_token_symbol(symbol)
+                    .add_metadata(DIVISIBILITY_METADATA_KEY, divisibility.to_string())
+                    .with_owner_rule(owner_rule);
+                if let Some(view_key) = view_key {
+                    builder = builder.with_view_key(view_key);
+                }
+                builder
+                    .initial_supply(ConfidentialOutputStatement::mint_revealed(initial_supply))
+                    .build_bucket()
+            } else {
+                assert!(view_key.is_none(), "only confidential resources have a view key");
+                ResourceBuilder::fungible()
+                    .with_metadata(metadata)
+                    .with_token_symbol(symbol)
+                    .add_metadata(DIVISIBILITY_METADATA_KEY, divisibility.to_string())
+                    .with_owner_rule(owner_rule)
+                    .initial_supply(initial_supply)
+                    .build_bucket()
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use tari_crypto::ristretto::RistrettoSecretKey;
+use tari_template_lib::args;
+use tari_template_lib::auth::OwnerRule;
+use tari_template_lib::prelude::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+use tari_wallet_lib::fungible_token::{created_resource_address, FungibleToken, FungibleTokenParams};
+use tari_wallet_lib::metadata::MetadataField;
+
+const VIEW_KEY: &str = "6e29fee6082e238475d40c6d4bed1d265ed5cd9d735a84a0380c09ff2d2e8d0d";
+
+struct Setup {
+    test: TemplateTest,
+    account: ComponentAddress,
+    owner_proof: NonFungibleAddress,
+    secret_key: RistrettoSecretKey,
+}
+
+fn setup() -> Setup {
+    let mut test = TemplateTest::new(["."]);
+    let (account, owner_proof, secret_key) = test.create_owned_account();
+    Setup {
+        test,
+        account,
+        owner_proof,
+        secret_key,
+    }
+}
+
+fn params(confidential: bool, view_key: Option<&str>) -> FungibleTokenParams {
+    FungibleTokenParams {
+        symbol: "TKN".to_string(),
+        divisibility: 6,
+        initial_supply: 1_000_000,
+        metadata: vec![MetadataField {
+            key: "name".to_string(),
+            value: "Token".to_string(),
+        }],
+        owner_rule: OwnerRule::OwnedBySigner,
+        confidential,
+        view_key: view_key.map(str::to_string),
+    }
+}
+
+// executes the instructions built by the wallet library, so their arguments must match the template ABI
+fn create_token(setup: &mut Setup, params: FungibleTokenParams) -> ResourceAddress {
+    let template_address = setup.test.get_template_address("FungibleToken");
+    let instructions = FungibleToken::new(params)
+        .unwrap()
+        .instructions(template_address, setup.account)
+        .unwrap();
+    let transaction = Transaction::builder()
+        .with_instructions(instructions)
+        .sign(&setup.secret_key)
+        .build();
+    let result = setup
+        .test
+        .execute_expect_success(transaction, vec![setup.owner_proof.clone()]);
+
+    created_resource_address(&result.finalize).unwrap()
+}
+
+fn balance(setup: &mut Setup, resource_address: ResourceAddress) -> Amount {
+    let transaction = Transaction::builder()
+        .call_method(setup.account, "balance", args![resource_address])
+        .sign(&setup.secret_key)
+        .build();
+    let result = setup
+        .test
+        .execute_expect_success(transaction, vec![setup.owner_proof.clone()]);
+    result.finalize.execution_results[0].decode().unwrap()
+}
+
+#[test]
+fn it_deposits_the_initial_supply_of_a_fungible_resource() {
+    let mut setup = setup();
+    let resource_address = create_token(&mut setup, params(false, None));
+
+    assert_eq!(balance(&mut setup, resource_address), Amount::new(1_000_000));
+}
+
+#[test]
+fn it_creates_confidential_resources_with_a_view_key() {
+    let mut setup = setup();
+    let resource_address = create_token(&mut setup, params(true, Some(VIEW_KEY)));
+
+    // the initial supply of confidential resources is revealed
+    assert_eq!(balance(&mut setup, resource_address), Amount::new(1_000_000));
+}